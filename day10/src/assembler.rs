@@ -0,0 +1,220 @@
+use std::{collections::HashMap, fmt};
+
+use crate::{
+    cpu::Instruction,
+    opcode::{OpcodeTable, Operand},
+};
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum AssembleError {
+    UnknownOpcode(usize, String),
+    InvalidOperand(usize, String),
+    WrongOperandCount(usize, String),
+    UnknownLabel(usize, String),
+    DuplicateLabel(usize, String),
+}
+
+impl fmt::Display for AssembleError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::UnknownOpcode(l, s) => write!(f, "line {}: unknown opcode '{}'", l, s),
+            Self::InvalidOperand(l, s) => write!(f, "line {}: invalid operand '{}'", l, s),
+            Self::WrongOperandCount(l, s) => {
+                write!(f, "line {}: wrong number of operands for '{}'", l, s)
+            }
+            Self::UnknownLabel(l, s) => write!(f, "line {}: unknown label '{}'", l, s),
+            Self::DuplicateLabel(l, s) => write!(f, "line {}: label '{}' already defined", l, s),
+        }
+    }
+}
+
+impl std::error::Error for AssembleError {}
+
+const COMMENT: char = ';';
+
+struct Line<'a> {
+    number: usize,
+    parts: Vec<&'a str>,
+}
+
+pub fn assemble(source: &str, opcodes: &OpcodeTable) -> Result<Vec<Instruction>, AssembleError> {
+    let mut labels: HashMap<&str, usize> = HashMap::new();
+    let mut lines: Vec<Line> = Vec::new();
+
+    for (i, raw) in source.lines().enumerate() {
+        let number = i + 1;
+        let mut code = raw.split(COMMENT).next().unwrap().trim();
+        while let Some((label, rest)) = code.split_once(':') {
+            let label = label.trim();
+            if label.is_empty() || label.contains(char::is_whitespace) {
+                return Err(AssembleError::InvalidOperand(number, label.to_string()));
+            }
+            if labels.insert(label, lines.len()).is_some() {
+                return Err(AssembleError::DuplicateLabel(number, label.to_string()));
+            }
+            code = rest.trim();
+        }
+        if !code.is_empty() {
+            lines.push(Line {
+                number,
+                parts: code.split_whitespace().collect(),
+            });
+        }
+    }
+
+    lines.iter().map(|l| decode(l, &labels, opcodes)).collect()
+}
+
+fn decode(
+    line: &Line,
+    labels: &HashMap<&str, usize>,
+    opcodes: &OpcodeTable,
+) -> Result<Instruction, AssembleError> {
+    let mnemonic = line.parts[0];
+    let operands = &line.parts[1..];
+    let wrong_count = || AssembleError::WrongOperandCount(line.number, mnemonic.to_string());
+    let value = |v: &str| {
+        v.parse::<i32>()
+            .map_err(|_| AssembleError::InvalidOperand(line.number, v.to_string()))
+    };
+
+    match (mnemonic, operands) {
+        ("noop", []) => Ok(Instruction::Noop),
+        ("addx", [v]) => Ok(Instruction::Addx(value(v)?)),
+        ("noop", _) | ("addx", _) => Err(wrong_count()),
+        _ => {
+            let index = opcodes
+                .find(mnemonic)
+                .ok_or_else(|| AssembleError::UnknownOpcode(line.number, mnemonic.to_string()))?;
+            match (opcodes.get(index).operand, operands) {
+                (Operand::None, []) => Ok(Instruction::Extended(index, 0)),
+                (Operand::Value, [v]) => Ok(Instruction::Extended(index, value(v)?)),
+                (Operand::Label, [l]) => match labels.get(l) {
+                    Some(target) => Ok(Instruction::Extended(index, *target as i32)),
+                    None => l
+                        .parse::<i32>()
+                        .map(|t| Instruction::Extended(index, t))
+                        .map_err(|_| AssembleError::UnknownLabel(line.number, l.to_string())),
+                },
+                _ => Err(wrong_count()),
+            }
+        }
+    }
+}
+
+pub fn disassemble(program: &[Instruction], opcodes: &OpcodeTable) -> String {
+    let mut targets: Vec<usize> = program
+        .iter()
+        .filter_map(|i| match i {
+            Instruction::Extended(o, t) if opcodes.get(*o).operand == Operand::Label => {
+                Some(*t as usize)
+            }
+            _ => None,
+        })
+        .collect();
+    targets.sort();
+    targets.dedup();
+
+    let mut result = String::new();
+    for index in 0..=program.len() {
+        if targets.contains(&index) {
+            result.push_str(&format!("L{}:\n", index));
+        }
+        let line = match program.get(index) {
+            Some(Instruction::Extended(o, t)) if opcodes.get(*o).operand == Operand::Label => {
+                format!("{} L{}", opcodes.get(*o).name, t)
+            }
+            Some(instruction) => opcodes.format(instruction),
+            None => continue,
+        };
+        result.push_str(&line);
+        result.push('\n');
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::fs;
+
+    use super::*;
+    use crate::opcode::{jmp, mulx};
+
+    fn extended() -> OpcodeTable {
+        let mut table = OpcodeTable::default();
+        table.register(mulx()).unwrap();
+        table.register(jmp()).unwrap();
+        table
+    }
+
+    #[test]
+    fn test_assemble() -> Result<(), String> {
+        let source = "\
+; double x and skip the noop
+start:  addx 2   ; x = 3
+        mulx 2
+        jmp end
+        noop
+end:";
+        let table = extended();
+        let program = assemble(source, &table).unwrap();
+
+        assert_eq!(
+            program,
+            vec![
+                Instruction::Addx(2),
+                Instruction::Extended(0, 2),
+                Instruction::Extended(1, 4),
+                Instruction::Noop,
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_assemble_errors() -> Result<(), String> {
+        let table = extended();
+
+        assert_eq!(
+            assemble("noop\nsubx 3", &table),
+            Err(AssembleError::UnknownOpcode(2, String::from("subx")))
+        );
+        assert_eq!(
+            assemble("addx three", &table),
+            Err(AssembleError::InvalidOperand(1, String::from("three")))
+        );
+        assert_eq!(
+            assemble("noop 1", &table),
+            Err(AssembleError::WrongOperandCount(1, String::from("noop")))
+        );
+        assert_eq!(
+            assemble("jmp nowhere", &table),
+            Err(AssembleError::UnknownLabel(1, String::from("nowhere")))
+        );
+        assert_eq!(
+            assemble("a: noop\na: noop", &table),
+            Err(AssembleError::DuplicateLabel(2, String::from("a")))
+        );
+        assert_eq!(
+            assemble("mulx 2", &OpcodeTable::default()),
+            Err(AssembleError::UnknownOpcode(1, String::from("mulx")))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_disassemble_round_trip() -> Result<(), String> {
+        let table = extended();
+        let program = assemble("addx 2\nloop: mulx 2\njmp loop\njmp done\ndone:", &table).unwrap();
+        let text = disassemble(&program, &table);
+
+        assert_eq!(text, "addx 2\nL1:\nmulx 2\njmp L1\njmp L4\nL4:\n");
+        assert_eq!(assemble(&text, &table).unwrap(), program);
+
+        let s = fs::read_to_string("test_input.txt").expect("File not found");
+        let program = assemble(&s, &table).unwrap();
+        assert_eq!(disassemble(&program, &table).trim_end(), s.trim_end());
+        Ok(())
+    }
+}
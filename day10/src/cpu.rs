@@ -0,0 +1,192 @@
+use std::fmt;
+
+use crate::{
+    assembler::{assemble, AssembleError},
+    opcode::OpcodeTable,
+};
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Instruction {
+    Noop,
+    Addx(i32),
+    Extended(usize, i32),
+}
+
+pub fn parse_program(s: &str) -> Result<Vec<Instruction>, AssembleError> {
+    assemble(s, &OpcodeTable::default())
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum CpuError {
+    UnknownOpcode(usize, usize),
+}
+
+impl fmt::Display for CpuError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::UnknownOpcode(pc, i) => {
+                write!(f, "instruction {}: opcode {} is not in the table", pc, i)
+            }
+        }
+    }
+}
+
+impl std::error::Error for CpuError {}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct CycleState {
+    pub cycle: i32,
+    pub regx: i32,
+}
+
+#[derive(Debug)]
+pub struct Cpu {
+    pub regx: i32,
+    pub cycle: i32,
+    pub pc: usize,
+    pub opcodes: OpcodeTable,
+    program: Vec<Instruction>,
+    remaining: u32,
+}
+
+impl Cpu {
+    pub fn new(program: Vec<Instruction>) -> Result<Self, CpuError> {
+        Self::with_opcodes(program, OpcodeTable::default())
+    }
+
+    // Extended instructions are indices into the table the program was
+    // assembled with, so a program run with the wrong table is refused here
+    // rather than panicking mid-run.
+    pub fn with_opcodes(program: Vec<Instruction>, opcodes: OpcodeTable) -> Result<Self, CpuError> {
+        for (pc, instruction) in program.iter().enumerate() {
+            if let Instruction::Extended(i, _) = *instruction {
+                if i >= opcodes.len() {
+                    return Err(CpuError::UnknownOpcode(pc, i));
+                }
+            }
+        }
+        Ok(Self {
+            regx: 1,
+            cycle: 0,
+            pc: 0,
+            opcodes,
+            program,
+            remaining: 0,
+        })
+    }
+
+    pub fn is_halted(&self) -> bool {
+        self.pc >= self.program.len()
+    }
+
+    pub fn current_instruction(&self) -> Option<Instruction> {
+        self.program.get(self.pc).copied()
+    }
+
+    pub fn at_instruction_start(&self) -> bool {
+        self.remaining == 0
+    }
+
+    // Runs one clock cycle. The returned state holds the register value *during*
+    // the cycle; an instruction's effect is only applied once its last cycle ends.
+    pub fn step(&mut self) -> Option<CycleState> {
+        if self.is_halted() {
+            return None;
+        }
+        let instruction = self.program[self.pc];
+        if self.remaining == 0 {
+            self.remaining = self.opcodes.cycles(&instruction);
+        }
+        self.cycle += 1;
+        let state = CycleState {
+            cycle: self.cycle,
+            regx: self.regx,
+        };
+        self.remaining -= 1;
+        if self.remaining == 0 {
+            self.pc += 1;
+            self.execute(instruction);
+        }
+        Some(state)
+    }
+
+    fn execute(&mut self, instruction: Instruction) {
+        match instruction {
+            Instruction::Noop => {}
+            Instruction::Addx(v) => self.regx += v,
+            Instruction::Extended(i, v) => (self.opcodes.get(i).effect)(self, v),
+        }
+    }
+}
+
+impl Iterator for Cpu {
+    type Item = CycleState;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.step()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::opcode::{jmp, mulx};
+
+    #[test]
+    fn test_parse_program() -> Result<(), String> {
+        let program = parse_program("noop\naddx 3\naddx -5").unwrap();
+        assert_eq!(
+            program,
+            vec![
+                Instruction::Noop,
+                Instruction::Addx(3),
+                Instruction::Addx(-5)
+            ]
+        );
+        assert!(parse_program("addx").is_err());
+        assert!(parse_program("addx x").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_process_add() -> Result<(), String> {
+        let mut cpu = Cpu::new(vec![Instruction::Addx(1)]).unwrap();
+        assert_eq!(cpu.step(), Some(CycleState { cycle: 1, regx: 1 }));
+        assert_eq!(cpu.step(), Some(CycleState { cycle: 2, regx: 1 }));
+        assert_eq!(cpu.step(), None);
+        assert_eq!(cpu.regx, 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_trace() -> Result<(), String> {
+        let program = parse_program("noop\naddx 3\naddx -5").unwrap();
+        let mut cpu = Cpu::new(program).unwrap();
+        let trace: Vec<_> = cpu.by_ref().map(|s| s.regx).collect();
+
+        assert_eq!(trace, vec![1, 1, 1, 4, 4]);
+        assert_eq!(cpu.regx, -1);
+        assert_eq!(cpu.cycle, 5);
+        assert!(cpu.is_halted());
+        Ok(())
+    }
+
+    #[test]
+    fn test_extended_opcodes() -> Result<(), String> {
+        let mut table = OpcodeTable::default();
+        table.register(mulx()).unwrap();
+        table.register(jmp()).unwrap();
+        let program = assemble("mulx 5\njmp end\naddx 100\nend: addx 1", &table).unwrap();
+        assert_eq!(
+            Cpu::new(program.clone()).err(),
+            Some(CpuError::UnknownOpcode(0, 0))
+        );
+        let mut cpu = Cpu::with_opcodes(program, table).unwrap();
+        let trace: Vec<_> = cpu.by_ref().map(|s| s.regx).collect();
+
+        assert_eq!(trace, vec![1, 1, 1, 5, 5, 5]);
+        assert_eq!(cpu.regx, 6);
+        Ok(())
+    }
+}
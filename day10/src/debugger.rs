@@ -0,0 +1,288 @@
+use std::io::{self, BufRead, Write};
+
+use crate::{
+    cpu::{Cpu, CycleState},
+    observer::{Crt, Observer, CRT_WIDTH},
+};
+
+const HELP: &str = "\
+commands:
+  s, step [n]          run n cycles (default 1)
+  c, continue          run until a breakpoint or the program halts
+  b, break cycle <n>   stop before cycle n starts
+  b, break instr <n>   stop before instruction n starts
+  d, delete            clear all breakpoints
+  w, watch             toggle reporting changes to x
+  p, print             show the cpu state for the next cycle
+  screen               show the crt drawn so far
+  q, quit              leave the debugger";
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Breakpoint {
+    Cycle(i32),
+    Instruction(usize),
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Command {
+    Step(usize),
+    Continue,
+    Break(Breakpoint),
+    Delete,
+    Watch,
+    Print,
+    Screen,
+    Help,
+    Quit,
+}
+
+impl Command {
+    pub fn parse(s: &str) -> Result<Self, String> {
+        let parts: Vec<_> = s.split_whitespace().collect();
+        let number = |v: &str| {
+            v.parse::<usize>()
+                .map_err(|_| format!("'{}' is not a valid number", v))
+        };
+        match parts[..] {
+            [] | ["s"] | ["step"] => Ok(Self::Step(1)),
+            ["s", n] | ["step", n] => Ok(Self::Step(number(n)?)),
+            ["c"] | ["continue"] => Ok(Self::Continue),
+            ["b", "cycle", n] | ["break", "cycle", n] => {
+                Ok(Self::Break(Breakpoint::Cycle(number(n)? as i32)))
+            }
+            ["b", "instr", n] | ["break", "instr", n] => {
+                Ok(Self::Break(Breakpoint::Instruction(number(n)?)))
+            }
+            ["d"] | ["delete"] => Ok(Self::Delete),
+            ["w"] | ["watch"] => Ok(Self::Watch),
+            ["p"] | ["print"] => Ok(Self::Print),
+            ["screen"] => Ok(Self::Screen),
+            ["h"] | ["help"] => Ok(Self::Help),
+            ["q"] | ["quit"] => Ok(Self::Quit),
+            _ => Err(format!("unknown command '{}', try 'help'", s.trim())),
+        }
+    }
+}
+
+pub struct Debugger {
+    pub cpu: Cpu,
+    pub crt: Crt,
+    breakpoints: Vec<Breakpoint>,
+    watch: bool,
+}
+
+impl Debugger {
+    pub fn new(cpu: Cpu) -> Self {
+        Self {
+            cpu,
+            crt: Crt::default(),
+            breakpoints: Vec::new(),
+            watch: false,
+        }
+    }
+
+    pub fn add_breakpoint(&mut self, breakpoint: Breakpoint) {
+        if !self.breakpoints.contains(&breakpoint) {
+            self.breakpoints.push(breakpoint);
+        }
+    }
+
+    pub fn at_breakpoint(&self) -> Option<Breakpoint> {
+        self.breakpoints.iter().copied().find(|b| match b {
+            Breakpoint::Cycle(c) => self.cpu.cycle + 1 == *c,
+            Breakpoint::Instruction(i) => self.cpu.pc == *i && self.cpu.at_instruction_start(),
+        })
+    }
+
+    pub fn step(&mut self) -> Option<CycleState> {
+        let state = self.cpu.step()?;
+        self.crt.observe(&state);
+        Some(state)
+    }
+
+    pub fn status(&self) -> String {
+        let instruction = match self.cpu.current_instruction() {
+            Some(i) => self.cpu.opcodes.format(&i),
+            None => String::from("halted"),
+        };
+        let beam = self.cpu.cycle % CRT_WIDTH as i32;
+        format!(
+            "cycle {} pc {} ({}) x={} sprite={}..={} beam={}",
+            self.cpu.cycle + 1,
+            self.cpu.pc,
+            instruction,
+            self.cpu.regx,
+            self.cpu.regx - 1,
+            self.cpu.regx + 1,
+            beam
+        )
+    }
+
+    fn describe(&self, state: &CycleState) -> String {
+        let beam = (state.cycle - 1) % CRT_WIDTH as i32;
+        let pixel = match (beam - state.regx).abs() <= 1 {
+            true => '#',
+            false => '.',
+        };
+        format!(
+            "cycle {} x={} sprite={}..={} beam={} pixel={}",
+            state.cycle,
+            state.regx,
+            state.regx - 1,
+            state.regx + 1,
+            beam,
+            pixel
+        )
+    }
+
+    fn run_cycles<W: Write>(&mut self, limit: Option<usize>, out: &mut W) -> io::Result<()> {
+        let mut count = 0;
+        loop {
+            let before = self.cpu.regx;
+            let state = match self.step() {
+                Some(state) => state,
+                None => return writeln!(out, "program halted"),
+            };
+            count += 1;
+            if limit.is_some() {
+                writeln!(out, "{}", self.describe(&state))?;
+            }
+            if self.watch && self.cpu.regx != before {
+                writeln!(
+                    out,
+                    "x changed {} -> {} after cycle {}",
+                    before, self.cpu.regx, state.cycle
+                )?;
+            }
+            if limit == Some(count) {
+                return Ok(());
+            }
+            if let Some(b) = self.at_breakpoint() {
+                return writeln!(out, "hit breakpoint {:?}: {}", b, self.status());
+            }
+        }
+    }
+
+    pub fn execute<W: Write>(&mut self, command: Command, out: &mut W) -> io::Result<bool> {
+        match command {
+            Command::Step(n) => self.run_cycles(Some(n.max(1)), out)?,
+            Command::Continue => self.run_cycles(None, out)?,
+            Command::Break(b) => {
+                self.add_breakpoint(b);
+                writeln!(out, "breakpoint set at {:?}", b)?;
+            }
+            Command::Delete => {
+                self.breakpoints.clear();
+                writeln!(out, "breakpoints cleared")?;
+            }
+            Command::Watch => {
+                self.watch = !self.watch;
+                writeln!(out, "watching x: {}", self.watch)?;
+            }
+            Command::Print => writeln!(out, "{}", self.status())?,
+            Command::Screen => write!(out, "{}", self.crt.render())?,
+            Command::Help => writeln!(out, "{}", HELP)?,
+            Command::Quit => return Ok(false),
+        }
+        Ok(true)
+    }
+
+    pub fn repl<R: BufRead, W: Write>(&mut self, input: R, mut out: W) -> io::Result<()> {
+        writeln!(out, "{}", self.status())?;
+        write!(out, "(debug) ")?;
+        out.flush()?;
+        for line in input.lines() {
+            let keep_going = match Command::parse(&line?) {
+                Ok(command) => self.execute(command, &mut out)?,
+                Err(e) => {
+                    writeln!(out, "{}", e)?;
+                    true
+                }
+            };
+            if !keep_going {
+                break;
+            }
+            write!(out, "(debug) ")?;
+            out.flush()?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::fs;
+
+    use super::*;
+    use crate::cpu::parse_program;
+
+    fn small_program() -> Debugger {
+        Debugger::new(Cpu::new(parse_program("noop\naddx 3\naddx -5").unwrap()).unwrap())
+    }
+
+    #[test]
+    fn test_parse_command() -> Result<(), String> {
+        assert_eq!(Command::parse("s")?, Command::Step(1));
+        assert_eq!(Command::parse("step 20")?, Command::Step(20));
+        assert_eq!(
+            Command::parse("b cycle 20")?,
+            Command::Break(Breakpoint::Cycle(20))
+        );
+        assert_eq!(
+            Command::parse("break instr 3")?,
+            Command::Break(Breakpoint::Instruction(3))
+        );
+        assert!(Command::parse("b cycle x").is_err());
+        assert!(Command::parse("jump").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_breakpoints() -> Result<(), String> {
+        let mut d = small_program();
+        let mut out = Vec::new();
+
+        d.add_breakpoint(Breakpoint::Instruction(2));
+        d.execute(Command::Continue, &mut out).unwrap();
+        assert_eq!(d.cpu.cycle, 3);
+        assert_eq!(d.cpu.regx, 4);
+
+        d.add_breakpoint(Breakpoint::Cycle(5));
+        d.execute(Command::Continue, &mut out).unwrap();
+        assert_eq!(d.cpu.cycle, 4);
+        assert_eq!(d.status(), "cycle 5 pc 2 (addx -5) x=4 sprite=3..=5 beam=4");
+
+        d.execute(Command::Continue, &mut out).unwrap();
+        assert!(d.cpu.is_halted());
+        assert_eq!(d.cpu.regx, -1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_repl() -> Result<(), String> {
+        let mut d = small_program();
+        let mut out = Vec::new();
+        d.repl("w\nstep 3\nq\nstep\n".as_bytes(), &mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+
+        assert!(out.contains("cycle 3 x=1 sprite=0..=2 beam=2 pixel=#"));
+        assert!(out.contains("x changed 1 -> 4 after cycle 3"));
+        assert_eq!(d.cpu.cycle, 3);
+        Ok(())
+    }
+
+    #[test]
+    fn test_screen() -> Result<(), String> {
+        let s = fs::read_to_string("test_input.txt").expect("File not found");
+        let mut d = Debugger::new(Cpu::new(parse_program(&s).unwrap()).unwrap());
+        let mut out = Vec::new();
+        d.execute(Command::Step(40), &mut out).unwrap();
+
+        let mut out = Vec::new();
+        d.execute(Command::Screen, &mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.starts_with("##..##..##..##..##..##..##..##..##..##..\n...."));
+        Ok(())
+    }
+}
@@ -0,0 +1,76 @@
+use cpu::{parse_program, Cpu};
+use observer::{Crt, Observer, SignalStrength};
+
+pub mod assembler;
+pub mod cpu;
+pub mod debugger;
+pub mod observer;
+pub mod ocr;
+pub mod opcode;
+
+pub fn run(cpu: &mut Cpu, observers: &mut [&mut dyn Observer]) {
+    for state in cpu {
+        for observer in observers.iter_mut() {
+            observer.observe(&state);
+        }
+    }
+}
+
+pub fn part1(s: &str) -> i32 {
+    let mut cpu = Cpu::new(parse_program(s).unwrap()).unwrap();
+    let mut strength = SignalStrength::default();
+    run(&mut cpu, &mut [&mut strength]);
+
+    strength.total()
+}
+
+pub fn draw(s: &str) -> String {
+    let mut cpu = Cpu::new(parse_program(s).unwrap()).unwrap();
+    let mut crt = Crt::default();
+    run(&mut cpu, &mut [&mut crt]);
+
+    crt.render()
+}
+
+pub fn part2(s: &str) -> String {
+    let mut cpu = Cpu::new(parse_program(s).unwrap()).unwrap();
+    let mut crt = Crt::default();
+    run(&mut cpu, &mut [&mut crt]);
+
+    ocr::read(&crt.pixels)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::fs;
+
+    use super::*;
+
+    #[test]
+    fn test_part1() -> Result<(), String> {
+        let s = fs::read_to_string("test_input.txt").expect("File not found");
+        let result = part1(&s);
+        assert_eq!(result, 13140);
+        Ok(())
+    }
+
+    #[test]
+    fn test_draw() -> Result<(), String> {
+        let s = fs::read_to_string("test_input.txt").expect("File not found");
+        let result = draw(&s);
+        let lines: Vec<_> = result.lines().collect();
+        assert_eq!(lines.len(), 6);
+        assert_eq!(lines[0], "##..##..##..##..##..##..##..##..##..##..");
+        assert_eq!(lines[5], "#######.......#######.......#######.....");
+        Ok(())
+    }
+
+    #[test]
+    fn test_part2() -> Result<(), String> {
+        let s = fs::read_to_string("input.txt").expect("File not found");
+        let result = part2(&s);
+        assert_eq!(result, "EALGULPG");
+        Ok(())
+    }
+}
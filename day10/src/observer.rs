@@ -0,0 +1,99 @@
+use crate::cpu::CycleState;
+
+pub const CRT_WIDTH: usize = 40;
+pub const CRT_HEIGHT: usize = 6;
+
+pub trait Observer {
+    fn observe(&mut self, state: &CycleState);
+}
+
+#[derive(Debug, Default)]
+pub struct SignalStrength {
+    pub strengths: Vec<i32>,
+}
+
+impl SignalStrength {
+    pub fn total(&self) -> i32 {
+        self.strengths.iter().sum()
+    }
+}
+
+impl Observer for SignalStrength {
+    fn observe(&mut self, state: &CycleState) {
+        if (state.cycle - 20) % CRT_WIDTH as i32 == 0 {
+            self.strengths.push(state.cycle * state.regx);
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Crt {
+    pub pixels: [[bool; CRT_WIDTH]; CRT_HEIGHT],
+}
+
+impl Default for Crt {
+    fn default() -> Self {
+        Self {
+            pixels: [[false; CRT_WIDTH]; CRT_HEIGHT],
+        }
+    }
+}
+
+impl Crt {
+    pub fn render(&self) -> String {
+        self.pixels
+            .iter()
+            .map(|row| {
+                let mut line: String = row.iter().map(|&p| if p { '#' } else { '.' }).collect();
+                line.push('\n');
+                line
+            })
+            .collect()
+    }
+}
+
+impl Observer for Crt {
+    fn observe(&mut self, state: &CycleState) {
+        let position = (state.cycle - 1) as usize;
+        let beam = position % CRT_WIDTH;
+        let row = (position / CRT_WIDTH) % CRT_HEIGHT;
+        self.pixels[row][beam] = (beam as i32 - state.regx).abs() <= 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_signal_strength() -> Result<(), String> {
+        let mut s = SignalStrength::default();
+        s.observe(&CycleState { cycle: 19, regx: 5 });
+        s.observe(&CycleState {
+            cycle: 20,
+            regx: 21,
+        });
+        s.observe(&CycleState {
+            cycle: 60,
+            regx: 19,
+        });
+
+        assert_eq!(s.strengths, vec![420, 1140]);
+        assert_eq!(s.total(), 1560);
+        Ok(())
+    }
+
+    #[test]
+    fn test_crt() -> Result<(), String> {
+        let mut crt = Crt::default();
+        crt.observe(&CycleState { cycle: 1, regx: 1 });
+        crt.observe(&CycleState { cycle: 2, regx: 1 });
+        crt.observe(&CycleState { cycle: 3, regx: 16 });
+
+        assert_eq!(crt.pixels[0][..4], [true, true, false, false]);
+        assert!(crt.render().starts_with("##..."));
+        assert_eq!(crt.render().lines().count(), CRT_HEIGHT);
+        Ok(())
+    }
+}
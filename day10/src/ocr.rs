@@ -0,0 +1,91 @@
+use crate::observer::{CRT_HEIGHT, CRT_WIDTH};
+
+const GLYPH_WIDTH: usize = 4;
+const GLYPH_SPACING: usize = 5;
+
+const GLYPHS: [(char, &str); 18] = [
+    ('A', ".##.#..##..######..##..#"),
+    ('B', "###.#..####.#..##..####."),
+    ('C', ".##.#..##...#...#..#.##."),
+    ('E', "#####...###.#...#...####"),
+    ('F', "#####...###.#...#...#..."),
+    ('G', ".##.#..##...#.###..#.###"),
+    ('H', "#..##..######..##..##..#"),
+    ('I', ".###..#...#...#...#..###"),
+    ('J', "..##...#...#...##..#.##."),
+    ('K', "#..##.#.##..#.#.#.#.#..#"),
+    ('L', "#...#...#...#...#...####"),
+    ('O', ".##.#..##..##..##..#.##."),
+    ('P', "###.#..##..####.#...#..."),
+    ('R', "###.#..##..####.#.#.#..#"),
+    ('S', ".####...#....##....####."),
+    ('U', "#..##..##..##..##..#.##."),
+    ('Z', "####...#..#..#..#...####"),
+    (' ', "........................"),
+];
+
+fn glyph_at(pixels: &[[bool; CRT_WIDTH]; CRT_HEIGHT], offset: usize) -> String {
+    pixels
+        .iter()
+        .flat_map(|row| row[offset..offset + GLYPH_WIDTH].iter())
+        .map(|&p| if p { '#' } else { '.' })
+        .collect()
+}
+
+pub fn read(pixels: &[[bool; CRT_WIDTH]; CRT_HEIGHT]) -> String {
+    (0..CRT_WIDTH / GLYPH_SPACING)
+        .map(|i| {
+            let glyph = glyph_at(pixels, i * GLYPH_SPACING);
+            GLYPHS
+                .iter()
+                .find(|(_, g)| *g == glyph)
+                .map_or('?', |(c, _)| *c)
+        })
+        .collect::<String>()
+        .trim_end()
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn to_pixels(s: &str) -> [[bool; CRT_WIDTH]; CRT_HEIGHT] {
+        let mut pixels = [[false; CRT_WIDTH]; CRT_HEIGHT];
+        for (y, line) in s.lines().enumerate() {
+            for (x, c) in line.chars().enumerate() {
+                pixels[y][x] = c == '#';
+            }
+        }
+        pixels
+    }
+
+    #[test]
+    fn test_read() -> Result<(), String> {
+        let screen = "\
+####..##..#.....##..#..#.#....###...##..
+#....#..#.#....#..#.#..#.#....#..#.#..#.
+###..#..#.#....#....#..#.#....#..#.#....
+#....####.#....#.##.#..#.#....###..#.##.
+#....#..#.#....#..#.#..#.#....#....#..#.
+####.#..#.####..###..##..####.#.....###.";
+
+        assert_eq!(read(&to_pixels(screen)), "EALGULPG");
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_unknown() -> Result<(), String> {
+        let screen = "\
+#.#..####
+.#...#...
+#.#..###.
+.#...#...
+#.#..#...
+.#...#...";
+
+        assert_eq!(read(&to_pixels(screen)), "?F");
+        Ok(())
+    }
+}
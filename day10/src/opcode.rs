@@ -0,0 +1,216 @@
+use std::fmt;
+
+use crate::cpu::{Cpu, Instruction};
+
+pub type Effect = fn(&mut Cpu, i32);
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Operand {
+    None,
+    Value,
+    Label,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum OpcodeError {
+    ZeroCycles(String),
+    Builtin(String),
+}
+
+impl fmt::Display for OpcodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::ZeroCycles(n) => write!(f, "opcode '{}' must take at least one cycle", n),
+            Self::Builtin(n) => write!(f, "'{}' is a builtin instruction", n),
+        }
+    }
+}
+
+impl std::error::Error for OpcodeError {}
+
+// The assembler decodes these itself, so an extended opcode with the same name
+// would never be used.
+const BUILTINS: [&str; 2] = ["noop", "addx"];
+
+#[derive(Debug, Clone)]
+pub struct Opcode {
+    pub name: String,
+    pub cycles: u32,
+    pub operand: Operand,
+    pub effect: Effect,
+}
+
+impl Opcode {
+    // Every instruction takes at least one cycle; the cpu counts down from
+    // `cycles` and would never finish one that takes none.
+    pub fn new(
+        name: &str,
+        cycles: u32,
+        operand: Operand,
+        effect: Effect,
+    ) -> Result<Self, OpcodeError> {
+        let opcode = Self {
+            name: name.to_string(),
+            cycles,
+            operand,
+            effect,
+        };
+        opcode.validate()?;
+        Ok(opcode)
+    }
+
+    fn validate(&self) -> Result<(), OpcodeError> {
+        if self.cycles == 0 {
+            return Err(OpcodeError::ZeroCycles(self.name.clone()));
+        }
+        if BUILTINS.contains(&self.name.as_str()) {
+            return Err(OpcodeError::Builtin(self.name.clone()));
+        }
+        Ok(())
+    }
+}
+
+pub fn mulx() -> Opcode {
+    Opcode {
+        name: String::from("mulx"),
+        cycles: 3,
+        operand: Operand::Value,
+        effect: |cpu, v| cpu.regx = cpu.regx.wrapping_mul(v),
+    }
+}
+
+// The cpu has already moved past the jmp when the effect runs, so the target is
+// simply the next instruction to fetch.
+pub fn jmp() -> Opcode {
+    Opcode {
+        name: String::from("jmp"),
+        cycles: 1,
+        operand: Operand::Label,
+        effect: |cpu, target| cpu.pc = target as usize,
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct OpcodeTable {
+    extended: Vec<Opcode>,
+}
+
+impl OpcodeTable {
+    // The fields are public, so an opcode built without `new` is checked here.
+    pub fn register(&mut self, opcode: Opcode) -> Result<usize, OpcodeError> {
+        opcode.validate()?;
+        Ok(match self.find(&opcode.name) {
+            Some(i) => {
+                self.extended[i] = opcode;
+                i
+            }
+            None => {
+                self.extended.push(opcode);
+                self.extended.len() - 1
+            }
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.extended.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.extended.is_empty()
+    }
+
+    pub fn find(&self, name: &str) -> Option<usize> {
+        self.extended.iter().position(|o| o.name == name)
+    }
+
+    pub fn get(&self, index: usize) -> &Opcode {
+        &self.extended[index]
+    }
+
+    pub fn cycles(&self, instruction: &Instruction) -> u32 {
+        match instruction {
+            Instruction::Noop => 1,
+            Instruction::Addx(_) => 2,
+            Instruction::Extended(i, _) => self.get(*i).cycles,
+        }
+    }
+
+    pub fn format(&self, instruction: &Instruction) -> String {
+        match instruction {
+            Instruction::Noop => String::from("noop"),
+            Instruction::Addx(v) => format!("addx {}", v),
+            Instruction::Extended(i, v) => {
+                let opcode = self.get(*i);
+                match opcode.operand {
+                    Operand::None => opcode.name.clone(),
+                    _ => format!("{} {}", opcode.name, v),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_register() -> Result<(), String> {
+        let mut table = OpcodeTable::default();
+        let m = table.register(mulx()).unwrap();
+        let j = table.register(jmp()).unwrap();
+
+        assert_eq!(table.find("jmp"), Some(j));
+        assert_eq!(table.register(mulx()), Ok(m));
+        assert_eq!(table.len(), 2);
+        assert_eq!(table.cycles(&Instruction::Extended(m, 2)), 3);
+        assert_eq!(table.cycles(&Instruction::Addx(2)), 2);
+        assert_eq!(table.format(&Instruction::Extended(m, 2)), "mulx 2");
+        assert_eq!(table.format(&Instruction::Noop), "noop");
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_opcodes() -> Result<(), String> {
+        let zero = OpcodeError::ZeroCycles(String::from("nop0"));
+        assert_eq!(
+            Opcode::new("nop0", 0, Operand::None, |_, _| ()).err(),
+            Some(zero)
+        );
+        assert_eq!(
+            Opcode::new("addx", 1, Operand::Value, |_, _| ()).err(),
+            Some(OpcodeError::Builtin(String::from("addx")))
+        );
+        assert!(Opcode::new("nop1", 1, Operand::None, |_, _| ()).is_ok());
+
+        let mut table = OpcodeTable::default();
+        assert_eq!(
+            table.register(Opcode {
+                cycles: 0,
+                ..mulx()
+            }),
+            Err(OpcodeError::ZeroCycles(String::from("mulx")))
+        );
+        assert_eq!(
+            table.register(Opcode {
+                name: String::from("noop"),
+                ..jmp()
+            }),
+            Err(OpcodeError::Builtin(String::from("noop")))
+        );
+        assert!(table.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_mulx_wraps() -> Result<(), String> {
+        let mut table = OpcodeTable::default();
+        let m = table.register(mulx()).unwrap();
+        let mut cpu = Cpu::with_opcodes(vec![Instruction::Extended(m, i32::MAX)], table).unwrap();
+        cpu.regx = 3;
+        cpu.by_ref().for_each(drop);
+        assert_eq!(cpu.regx, 3i32.wrapping_mul(i32::MAX));
+        Ok(())
+    }
+}
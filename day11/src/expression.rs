@@ -0,0 +1,285 @@
+use std::{fmt, str::FromStr};
+
+use crate::worry::{ArithmeticError, Worry};
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Operator {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+}
+
+impl Operator {
+    fn precedence(&self) -> u8 {
+        match self {
+            Self::Add | Self::Subtract => 1,
+            Self::Multiply | Self::Divide => 2,
+        }
+    }
+
+    fn from_token(token: &str) -> Option<Self> {
+        match token {
+            "+" => Some(Self::Add),
+            "-" => Some(Self::Subtract),
+            "*" => Some(Self::Multiply),
+            "/" => Some(Self::Divide),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Operator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let symbol = match self {
+            Self::Add => "+",
+            Self::Subtract => "-",
+            Self::Multiply => "*",
+            Self::Divide => "/",
+        };
+        write!(f, "{}", symbol)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum Expression {
+    Old,
+    Constant(i64),
+    Binary(Box<Expression>, Operator, Box<Expression>),
+}
+
+impl Expression {
+    pub fn try_evaluate<W: Worry>(&self, old: &W) -> Result<W, ArithmeticError> {
+        match self {
+            Self::Old => Ok(old.clone()),
+            Self::Constant(c) => Ok(W::from_i64(*c)),
+            Self::Binary(a, op, b) => W::combine(*op, &a.try_evaluate(old)?, &b.try_evaluate(old)?),
+        }
+    }
+
+    /// Whether results stay congruent modulo any number when `old` is replaced
+    /// by something congruent to it. Division is the only operator that breaks
+    /// that.
+    pub fn preserves_congruence(&self) -> bool {
+        match self {
+            Self::Old | Self::Constant(_) => true,
+            Self::Binary(_, Operator::Divide, _) => false,
+            Self::Binary(a, _, b) => a.preserves_congruence() && b.preserves_congruence(),
+        }
+    }
+
+    fn precedence(&self) -> u8 {
+        match self {
+            Self::Binary(_, op, _) => op.precedence(),
+            _ => u8::MAX,
+        }
+    }
+}
+
+impl fmt::Display for Expression {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Old => write!(f, "old"),
+            Self::Constant(c) => write!(f, "{}", c),
+            Self::Binary(a, op, b) => {
+                match a.precedence() < op.precedence() {
+                    true => write!(f, "({})", a)?,
+                    false => write!(f, "{}", a)?,
+                }
+                write!(f, " {} ", op)?;
+                match b.precedence() <= op.precedence() {
+                    true => write!(f, "({})", b),
+                    false => write!(f, "{}", b),
+                }
+            }
+        }
+    }
+}
+
+fn tokenize(s: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    for c in s.chars() {
+        if c.is_alphanumeric() {
+            current.push(c);
+            continue;
+        }
+        if !current.is_empty() {
+            tokens.push(current.clone());
+            current.clear();
+        }
+        if !c.is_whitespace() {
+            tokens.push(c.to_string());
+        }
+    }
+    if !current.is_empty() {
+        tokens.push(current);
+    }
+    tokens
+}
+
+struct Parser {
+    tokens: Vec<String>,
+    position: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&str> {
+        self.tokens.get(self.position).map(|t| t.as_str())
+    }
+
+    fn next(&mut self) -> Result<String, String> {
+        let token = self
+            .tokens
+            .get(self.position)
+            .cloned()
+            .ok_or_else(|| String::from("unexpected end of expression"))?;
+        self.position += 1;
+        Ok(token)
+    }
+
+    fn expression(&mut self, min_precedence: u8) -> Result<Expression, String> {
+        let mut left = self.operand()?;
+        while let Some(op) = self.peek().and_then(Operator::from_token) {
+            if op.precedence() < min_precedence {
+                break;
+            }
+            self.position += 1;
+            let right = self.expression(op.precedence() + 1)?;
+            left = Expression::Binary(Box::new(left), op, Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn operand(&mut self) -> Result<Expression, String> {
+        let token = self.next()?;
+        match token.as_str() {
+            "old" => Ok(Expression::Old),
+            "(" => {
+                let inner = self.expression(0)?;
+                match self.next()?.as_str() {
+                    ")" => Ok(inner),
+                    t => Err(format!("expected ')' but found '{}'", t)),
+                }
+            }
+            // A minus in operand position belongs to the literal after it, so
+            // negative constants print and parse back the same way.
+            "-" => {
+                let literal = format!("-{}", self.next()?);
+                literal
+                    .parse::<i64>()
+                    .map(Expression::Constant)
+                    .map_err(|_| format!("'{}' is not a valid operand", literal))
+            }
+            t => t
+                .parse::<i64>()
+                .map(Expression::Constant)
+                .map_err(|_| format!("'{}' is not a valid operand", t)),
+        }
+    }
+}
+
+impl FromStr for Expression {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser {
+            tokens: tokenize(s),
+            position: 0,
+        };
+        let expression = parser.expression(0)?;
+        match parser.peek() {
+            None => Ok(expression),
+            Some(t) => Err(format!("unexpected '{}' in '{}'", t, s)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_parse() -> Result<(), String> {
+        let e: Expression = "old * 19".parse()?;
+        assert_eq!(
+            e,
+            Expression::Binary(
+                Box::new(Expression::Old),
+                Operator::Multiply,
+                Box::new(Expression::Constant(19))
+            )
+        );
+        assert!("old *".parse::<Expression>().is_err());
+        assert!("old ^ 2".parse::<Expression>().is_err());
+        assert!("(old + 1".parse::<Expression>().is_err());
+
+        let e: Expression = "old * -1".parse()?;
+        assert_eq!(
+            e,
+            Expression::Binary(
+                Box::new(Expression::Old),
+                Operator::Multiply,
+                Box::new(Expression::Constant(-1))
+            )
+        );
+        assert!("old * -old".parse::<Expression>().is_err());
+        assert!("old * -".parse::<Expression>().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_evaluate() -> Result<(), String> {
+        let e: Expression = "3 + old * (old - 1) / 2".parse()?;
+        assert_eq!(e.try_evaluate(&5_i64), Ok(13));
+
+        let e: Expression = "10 - old - 2".parse()?;
+        assert_eq!(e.try_evaluate(&3_i64), Ok(5));
+        Ok(())
+    }
+
+    #[test]
+    fn test_display_round_trip() -> Result<(), String> {
+        for s in [
+            "old * old",
+            "(old + 3) * 2",
+            "old - (old - 2)",
+            "2 * old / (3 * old)",
+            "old - -1",
+            "-9223372036854775808 * old",
+        ] {
+            let e: Expression = s.parse()?;
+            assert_eq!(e.to_string(), s);
+            assert_eq!(e.to_string().parse::<Expression>()?, e);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_preserves_congruence() -> Result<(), String> {
+        for (s, expected) in [
+            ("old * old + 3", true),
+            ("(old - 1) * -2", true),
+            ("old / 2", false),
+            ("old * (old / 3 + 1)", false),
+        ] {
+            assert_eq!(s.parse::<Expression>()?.preserves_congruence(), expected);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_try_evaluate() -> Result<(), String> {
+        let e: Expression = "old * old".parse()?;
+        assert_eq!(e.try_evaluate(&3_i64), Ok(9));
+        assert_eq!(
+            e.try_evaluate(&4_000_000_000_i64),
+            Err(ArithmeticError::Overflow)
+        );
+
+        let e: Expression = "old / (old - 3)".parse()?;
+        assert_eq!(e.try_evaluate(&3_i64), Err(ArithmeticError::DivisionByZero));
+        Ok(())
+    }
+}
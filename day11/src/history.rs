@@ -0,0 +1,284 @@
+use std::{collections::HashMap, error::Error, fmt};
+
+use crate::{worry::Worry, MonkeyBusiness, MonkeyError};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Round<W> {
+    pub inspections: Vec<usize>,
+    pub holdings: Vec<Vec<W>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hop<W> {
+    pub round: usize,
+    pub from: usize,
+    pub to: usize,
+    pub worry: W,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum TraceError {
+    NoSuchItem(usize, usize),
+    Monkey(MonkeyError),
+}
+
+impl fmt::Display for TraceError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::NoSuchItem(m, i) => write!(f, "monkey {} has no item {}", m, i),
+            Self::Monkey(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl Error for TraceError {}
+
+impl From<MonkeyError> for TraceError {
+    fn from(e: MonkeyError) -> Self {
+        Self::Monkey(e)
+    }
+}
+
+impl<W: Worry> MonkeyBusiness<W> {
+    pub fn run_with_history(&mut self, rounds: usize) -> Result<Vec<Round<W>>, MonkeyError> {
+        let mut history = Vec::with_capacity(rounds);
+        for _ in 0..rounds {
+            let before = self.counts.clone();
+            self.run_cycle()?;
+            history.push(Round {
+                inspections: self.counts.iter().zip(before).map(|(a, b)| a - b).collect(),
+                holdings: self.monkies.iter().map(|m| m.items.clone()).collect(),
+            });
+        }
+        Ok(history)
+    }
+
+    // Items never interact, so a single item can be followed on its own. It keeps
+    // moving within a round while it is thrown to higher numbered monkeys; a
+    // monkey throwing to itself has already finished its turn.
+    fn item_round(&self, monkey: usize, item: &W) -> Result<Vec<Hop<W>>, MonkeyError> {
+        let mut hops = Vec::new();
+        let mut from = monkey;
+        let mut item = item.clone();
+        loop {
+            let throw = self.monkies[from].inspect(&item)?;
+            item = self.reduce(&throw.item);
+            hops.push(Hop {
+                round: 0,
+                from,
+                to: throw.target,
+                worry: item.clone(),
+            });
+            if throw.target <= from {
+                return Ok(hops);
+            }
+            from = throw.target;
+        }
+    }
+
+    pub fn trace_item(
+        &self,
+        monkey: usize,
+        index: usize,
+        rounds: usize,
+    ) -> Result<Vec<Hop<W>>, TraceError> {
+        let mut path = Vec::new();
+        let mut item = self
+            .monkies
+            .get(monkey)
+            .and_then(|m| m.items.get(index))
+            .ok_or(TraceError::NoSuchItem(monkey, index))?
+            .clone();
+        let mut monkey = monkey;
+        for round in 1..=rounds {
+            let hops = self.item_round(monkey, &item)?;
+            let last = hops.last().unwrap();
+            monkey = last.to;
+            item = last.worry.clone();
+            path.extend(hops.into_iter().map(|h| Hop { round, ..h }));
+        }
+        Ok(path)
+    }
+
+    // Follows one item round by round until its (monkey, reduced worry) state
+    // repeats, then multiplies the inspections of the cycle out to `rounds`.
+    fn item_inspections(
+        &self,
+        monkey: usize,
+        item: &W,
+        rounds: u64,
+    ) -> Result<Vec<u64>, MonkeyError> {
+        let mut seen: HashMap<(usize, W), u64> = HashMap::new();
+        let mut per_round: Vec<Vec<usize>> = Vec::new();
+        let mut state = (monkey, item.clone());
+        let mut cycle_start = None;
+
+        for round in 0..rounds {
+            if let Some(&start) = seen.get(&state) {
+                cycle_start = Some(start);
+                break;
+            }
+            seen.insert(state.clone(), round);
+            let hops = self.item_round(state.0, &state.1)?;
+            per_round.push(hops.iter().map(|h| h.from).collect());
+            let last = hops.last().unwrap();
+            state = (last.to, last.worry.clone());
+        }
+
+        let mut totals = vec![0; self.monkies.len()];
+        let mut add = |rounds: &[Vec<usize>], times: u64| {
+            rounds.iter().flatten().for_each(|&m| totals[m] += times);
+        };
+        match cycle_start {
+            None => add(&per_round, 1),
+            Some(start) => {
+                let start = start as usize;
+                let cycle = &per_round[start..];
+                let remaining = rounds - start as u64;
+                let length = cycle.len() as u64;
+                add(&per_round[..start], 1);
+                add(cycle, remaining / length);
+                add(&cycle[..(remaining % length) as usize], 1);
+            }
+        }
+        Ok(totals)
+    }
+
+    pub fn extrapolate(&self, rounds: u64) -> Result<Vec<u64>, MonkeyError> {
+        let mut totals = vec![0; self.monkies.len()];
+        for (monkey, m) in self.monkies.iter().enumerate() {
+            for item in m.items.iter() {
+                let counts = self.item_inspections(monkey, item, rounds)?;
+                totals.iter_mut().zip(counts).for_each(|(t, c)| *t += c);
+            }
+        }
+        Ok(totals)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::fs;
+
+    use super::*;
+
+    #[test]
+    fn test_run_with_history() -> Result<(), String> {
+        let s = fs::read_to_string("test_input.txt").expect("File not found");
+        let mut business = MonkeyBusiness::new(&s, 3);
+        let history = business.run_with_history(20).unwrap();
+
+        assert_eq!(history.len(), 20);
+        assert_eq!(history[0].inspections, vec![2, 4, 3, 5]);
+        assert_eq!(
+            history[0].holdings,
+            vec![
+                vec![20, 23, 27, 26],
+                vec![2080, 25, 167, 207, 401, 1046],
+                vec![],
+                vec![]
+            ]
+        );
+        let totals: Vec<usize> = (0..4)
+            .map(|m| history.iter().map(|r| r.inspections[m]).sum())
+            .collect();
+        assert_eq!(totals, vec![101, 95, 7, 105]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_trace_item() -> Result<(), String> {
+        let s = fs::read_to_string("test_input.txt").expect("File not found");
+        let business = MonkeyBusiness::new(&s, 3);
+        let path = business.trace_item(0, 0, 1).unwrap();
+
+        assert_eq!(
+            path,
+            vec![
+                Hop {
+                    round: 1,
+                    from: 0,
+                    to: 3,
+                    worry: 500
+                },
+                Hop {
+                    round: 1,
+                    from: 3,
+                    to: 1,
+                    worry: 167
+                },
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_trace_missing_item() -> Result<(), String> {
+        let s = fs::read_to_string("test_input.txt").expect("File not found");
+        let business = MonkeyBusiness::new(&s, 3);
+
+        assert_eq!(
+            business.trace_item(0, 2, 1),
+            Err(TraceError::NoSuchItem(0, 2))
+        );
+        assert_eq!(
+            business.trace_item(4, 0, 1),
+            Err(TraceError::NoSuchItem(4, 0))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_extrapolate() -> Result<(), String> {
+        let s = fs::read_to_string("test_input.txt").expect("File not found");
+        let business = MonkeyBusiness::new(&s, 1);
+
+        assert_eq!(business.extrapolate(20).unwrap(), vec![99, 97, 8, 103]);
+        assert_eq!(
+            business.extrapolate(10000).unwrap(),
+            vec![52166, 47830, 1938, 52013]
+        );
+
+        let mut simulated = business.clone();
+        simulated.run(123456).unwrap();
+        let counts: Vec<u64> = simulated.counts.iter().map(|&c| c as u64).collect();
+        assert_eq!(business.extrapolate(123456).unwrap(), counts);
+        Ok(())
+    }
+
+    #[test]
+    fn test_self_throw() -> Result<(), String> {
+        let s = "\
+Monkey 0:
+  Starting items: 4
+  Operation: new = old + 2
+  Test: divisible by 2
+    If true: throw to monkey 0
+    If false: throw to monkey 1
+
+Monkey 1:
+  Starting items: 1
+  Operation: new = old * 1
+  Test: divisible by 3
+    If true: throw to monkey 0
+    If false: throw to monkey 0";
+        let business = MonkeyBusiness::new(s, 1);
+
+        assert_eq!(
+            business.trace_item(0, 0, 1).unwrap(),
+            vec![Hop {
+                round: 1,
+                from: 0,
+                to: 0,
+                worry: 0
+            }]
+        );
+
+        let mut simulated = business.clone();
+        simulated.run(100).unwrap();
+        let counts: Vec<u64> = simulated.counts.iter().map(|&c| c as u64).collect();
+        assert_eq!(business.extrapolate(100).unwrap(), counts);
+        Ok(())
+    }
+}
@@ -0,0 +1,565 @@
+use std::{error::Error, fmt, str::FromStr};
+
+use expression::Expression;
+use num_bigint::BigInt;
+use policy::{Relief, Scoring, TopProduct};
+use worry::{lcm, ArithmeticError, Worry};
+
+pub mod expression;
+pub mod history;
+pub mod policy;
+pub mod worry;
+
+#[derive(Debug, PartialEq, Eq)]
+struct Throw<W> {
+    target: usize,
+    item: W,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct MonkeyError {
+    pub monkey: usize,
+    pub item: String,
+    pub error: ArithmeticError,
+}
+
+impl fmt::Display for MonkeyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "monkey {} hit {:?} inspecting item {}",
+            self.monkey, self.error, self.item
+        )
+    }
+}
+
+impl Error for MonkeyError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Monkey<W = i64> {
+    id: usize,
+    items: Vec<W>,
+    operation: Expression,
+    test: i64,
+    true_target: usize,
+    false_target: usize,
+    relief: Relief,
+}
+
+impl Monkey {
+    fn new(s: &str, worry: i64) -> Self {
+        Monkey::parse(s, Relief::Divide(worry))
+    }
+}
+
+impl<W: Worry> Monkey<W> {
+    fn parse(s: &str, relief: Relief) -> Self {
+        let mut lines = s.lines();
+        let id = lines
+            .next()
+            .unwrap()
+            .trim()
+            .strip_prefix("Monkey ")
+            .unwrap()
+            .trim_end_matches(':')
+            .parse()
+            .unwrap();
+        Self {
+            id,
+            items: parse_items(lines.next().unwrap())
+                .into_iter()
+                .map(W::from_i64)
+                .collect(),
+            operation: parse_operation(lines.next().unwrap()),
+            test: parse_to_int(lines.next().unwrap(), "Test: divisible by "),
+            true_target: parse_to_int(lines.next().unwrap(), "If true: throw to monkey "),
+            false_target: parse_to_int(lines.next().unwrap(), "If false: throw to monkey "),
+            relief,
+        }
+    }
+
+    fn inspect(&self, item: &W) -> Result<Throw<W>, MonkeyError> {
+        let new_item = self
+            .operation
+            .try_evaluate(item)
+            .and_then(|new_item| self.relief.apply(new_item))
+            .map_err(|error| MonkeyError {
+                monkey: self.id,
+                item: item.to_string(),
+                error,
+            })?;
+        let target = match new_item.is_divisible_by(self.test) {
+            true => self.true_target,
+            false => self.false_target,
+        };
+        Ok(Throw {
+            item: new_item,
+            target,
+        })
+    }
+
+    fn throw(&mut self) -> Result<Vec<Throw<W>>, MonkeyError> {
+        let throws = self
+            .items
+            .iter()
+            .map(|item| self.inspect(item))
+            .collect::<Result<Vec<_>, _>>()?;
+        self.items = Vec::new();
+        Ok(throws)
+    }
+}
+
+impl<W: Worry> fmt::Display for Monkey<W> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let items: Vec<_> = self.items.iter().map(|i| i.to_string()).collect();
+        writeln!(f, "Monkey {}:", self.id)?;
+        writeln!(f, "  Starting items: {}", items.join(", "))?;
+        writeln!(f, "  Operation: new = {}", self.operation)?;
+        writeln!(f, "  Test: divisible by {}", self.test)?;
+        writeln!(f, "    If true: throw to monkey {}", self.true_target)?;
+        write!(f, "    If false: throw to monkey {}", self.false_target)
+    }
+}
+
+fn parse_items(s: &str) -> Vec<i64> {
+    let prefix = "Starting items: ";
+
+    s.trim()
+        .strip_prefix(prefix)
+        .unwrap()
+        .split(", ")
+        .map(|x| x.parse().unwrap())
+        .collect()
+}
+
+fn parse_operation(s: &str) -> Expression {
+    let prefix = "Operation: new = ";
+    s.trim().strip_prefix(prefix).unwrap().parse().unwrap()
+}
+
+fn parse_to_int<T>(s: &str, prefix: &str) -> T
+where
+    T: FromStr,
+{
+    s.trim()
+        .strip_prefix(prefix)
+        .unwrap()
+        .parse::<T>()
+        .ok()
+        .unwrap()
+}
+
+fn create_monkies(s: &str, worry: i64) -> Vec<Monkey> {
+    s.split("\n\n").map(|m| Monkey::new(m, worry)).collect()
+}
+
+fn parse_monkies<W: Worry>(s: &str, relief: &Relief) -> Vec<Monkey<W>> {
+    s.split("\n\n")
+        .map(|m| Monkey::parse(m, relief.clone()))
+        .collect()
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MonkeyBusiness<W = i64> {
+    monkies: Vec<Monkey<W>>,
+    counts: Vec<usize>,
+    common_divider: Option<i64>,
+}
+
+impl MonkeyBusiness {
+    pub fn new(s: &str, worry: i64) -> Self {
+        MonkeyBusiness::from_monkies(create_monkies(s, worry), true)
+    }
+}
+
+impl MonkeyBusiness<BigInt> {
+    pub fn unreduced(s: &str, worry: i64) -> Self {
+        MonkeyBusiness::from_monkies(parse_monkies(s, &Relief::Divide(worry)), false)
+    }
+}
+
+impl<W: Worry> MonkeyBusiness<W> {
+    pub fn with_relief(s: &str, relief: Relief) -> Self {
+        MonkeyBusiness::from_monkies(parse_monkies(s, &relief), true)
+    }
+
+    // Worry values only matter modulo each monkey's test, so reducing by the lcm
+    // of the tests keeps every decision intact, as long as every monkey's
+    // operation and relief do too. If even the lcm overflows we skip reduction
+    // and let the checked arithmetic report it.
+    fn from_monkies(monkies: Vec<Monkey<W>>, reduce: bool) -> Self {
+        let len = monkies.len();
+        let common_divider = match reduce {
+            true => lcm(monkies.iter().map(|m| m.test)).filter(|&d| {
+                monkies
+                    .iter()
+                    .all(|m| m.operation.preserves_congruence() && m.relief.keeps_remainder(d))
+            }),
+            false => None,
+        };
+        Self {
+            monkies,
+            counts: vec![0; len],
+            common_divider,
+        }
+    }
+
+    pub fn run(&mut self, iterations: i64) -> Result<usize, MonkeyError> {
+        self.run_scored(iterations, &TopProduct(2))
+    }
+
+    pub fn run_scored<S: Scoring>(
+        &mut self,
+        iterations: i64,
+        scoring: &S,
+    ) -> Result<S::Output, MonkeyError> {
+        for _ in 0..iterations {
+            self.run_cycle()?;
+        }
+
+        Ok(scoring.score(&self.counts))
+    }
+
+    fn run_cycle(&mut self) -> Result<(), MonkeyError> {
+        for i in 0..self.monkies.len() {
+            let throws = self.monkies[i].throw()?;
+            self.counts[i] += throws.len();
+            self.process_throws(throws);
+        }
+        Ok(())
+    }
+
+    fn process_throws(&mut self, throws: Vec<Throw<W>>) {
+        for throw in throws.iter() {
+            self.process_throw(throw);
+        }
+    }
+
+    fn process_throw(&mut self, throw: &Throw<W>) {
+        let item = self.reduce(&throw.item);
+        self.monkies[throw.target].items.push(item);
+    }
+
+    fn reduce(&self, item: &W) -> W {
+        match self.common_divider {
+            Some(divider) => item.reduce(divider),
+            None => item.clone(),
+        }
+    }
+}
+
+impl<W: Worry> fmt::Display for MonkeyBusiness<W> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let monkies: Vec<_> = self.monkies.iter().map(|m| m.to_string()).collect();
+        write!(f, "{}", monkies.join("\n\n"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::fs;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_items() -> Result<(), String> {
+        let s = "Starting items: 75, 64";
+        let result = parse_items(s);
+        assert_eq!(result.len(), 2);
+        assert_eq!(result[0], 75);
+        assert_eq!(result[1], 64);
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_operation() -> Result<(), String> {
+        let s = "Operation: new = old * 13";
+        let result = parse_operation(s);
+        assert_eq!(result.try_evaluate(&2_i64), Ok(26));
+
+        let s = "Operation: new = old * old";
+        let result = parse_operation(s);
+        assert_eq!(result.try_evaluate(&2_i64), Ok(4));
+
+        let s = "Operation: new = old + 13";
+        let result = parse_operation(s);
+        assert_eq!(result.try_evaluate(&2_i64), Ok(15));
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_to_int() -> Result<(), String> {
+        assert_eq!(
+            parse_to_int::<i64>("Test: divisible by 19", "Test: divisible by "),
+            19
+        );
+        assert_eq!(
+            parse_to_int::<usize>("If true: throw to monkey 2", "If true: throw to monkey "),
+            2
+        );
+        assert_eq!(
+            parse_to_int::<usize>("If false: throw to monkey 7", "If false: throw to monkey "),
+            7
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_create_monkey() -> Result<(), String> {
+        let s = "Monkey 1:
+          Starting items: 50, 99, 80, 84, 65, 95
+          Operation: new = old + 2
+          Test: divisible by 3
+            If true: throw to monkey 4
+            If false: throw to monkey 5";
+
+        let monkey = Monkey::new(s, 3);
+
+        assert_eq!(monkey.items.len(), 6);
+        assert_eq!(monkey.operation.try_evaluate(&3_i64), Ok(5));
+        assert_eq!(monkey.test, 3);
+        assert_eq!(monkey.true_target, 4);
+        assert_eq!(monkey.false_target, 5);
+        Ok(())
+    }
+
+    #[test]
+    fn test_create_monkies() -> Result<(), String> {
+        let s = fs::read_to_string("test_input.txt").expect("File not found");
+        let monkies = create_monkies(&s, 3);
+        assert_eq!(monkies.len(), 4);
+        Ok(())
+    }
+
+    #[test]
+    fn test_monkey_throw() -> Result<(), String> {
+        let s = fs::read_to_string("test_input.txt").expect("File not found");
+        let mut monkies = create_monkies(&s, 3);
+
+        let throws = monkies[0].throw().unwrap();
+
+        assert_eq!(throws.len(), 2);
+        assert_eq!(monkies[0].items.len(), 0);
+        assert_eq!(
+            throws[0],
+            Throw {
+                target: 3,
+                item: 500
+            }
+        );
+
+        let throws = monkies[2].throw().unwrap();
+        assert_eq!(
+            throws[0],
+            Throw {
+                target: 1,
+                item: 2080
+            }
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_part1_process_throw() -> Result<(), String> {
+        let s = fs::read_to_string("test_input.txt").expect("File not found");
+        let mut part1 = MonkeyBusiness::new(&s, 3);
+        let throws = part1.monkies[0].throw().unwrap();
+        part1.process_throw(&throws[0]);
+
+        assert_eq!(part1.monkies[3].items.len(), 2);
+        assert_eq!(*part1.monkies[3].items.last().unwrap(), 500);
+        Ok(())
+    }
+
+    #[test]
+    fn test_part1() -> Result<(), String> {
+        let s = fs::read_to_string("test_input.txt").expect("File not found");
+        let mut part1 = MonkeyBusiness::new(&s, 3);
+        let result = part1.run(20).unwrap();
+
+        assert_eq!(part1.counts, vec![101, 95, 7, 105]);
+        assert_eq!(result, 10605);
+        Ok(())
+    }
+
+    #[test]
+    fn test_part2() -> Result<(), String> {
+        let s = fs::read_to_string("test_input.txt").expect("File not found");
+        let mut part1 = MonkeyBusiness::new(&s, 1);
+        let result = part1.run(10000).unwrap();
+
+        assert_eq!(part1.counts, vec![52166, 47830, 1938, 52013]);
+        assert_eq!(result, 2713310158);
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_nested_operation() -> Result<(), String> {
+        let s = "Operation: new = (old + 3) * old";
+        let result = parse_operation(s);
+        assert_eq!(result.try_evaluate(&2_i64), Ok(10));
+        assert_eq!(result.to_string(), "(old + 3) * old");
+        Ok(())
+    }
+
+    #[test]
+    fn test_serialize_round_trip() -> Result<(), String> {
+        let s = fs::read_to_string("test_input.txt").expect("File not found");
+        let business = MonkeyBusiness::new(&s, 3);
+        let serialized = business.to_string();
+
+        assert_eq!(serialized.trim_end(), s.trim_end());
+        assert_eq!(MonkeyBusiness::new(&serialized, 3), business);
+
+        let mut copy = business.clone();
+        copy.run(1).unwrap();
+        assert_ne!(copy, business);
+        Ok(())
+    }
+
+    #[test]
+    fn test_unreduced_matches_reduced() -> Result<(), String> {
+        let s = fs::read_to_string("test_input.txt").expect("File not found");
+        let mut reduced = MonkeyBusiness::new(&s, 1);
+        let mut unreduced = MonkeyBusiness::unreduced(&s, 1);
+
+        assert_eq!(reduced.run(20), unreduced.run(20));
+        assert_eq!(reduced.counts, unreduced.counts);
+        assert_eq!(reduced.counts, vec![99, 97, 8, 103]);
+
+        let mut part1 = MonkeyBusiness::unreduced(&s, 3);
+        assert_eq!(part1.run(20), Ok(10605));
+        Ok(())
+    }
+
+    #[test]
+    fn test_division_blocks_reduction() -> Result<(), String> {
+        // Halving does not respect remainders, so worry has to stay whole.
+        let s = "\
+Monkey 0:
+  Starting items: 79, 98
+  Operation: new = old / 2
+  Test: divisible by 5
+    If true: throw to monkey 1
+    If false: throw to monkey 2
+
+Monkey 1:
+  Starting items: 54, 65, 75
+  Operation: new = old * 7 + 3
+  Test: divisible by 3
+    If true: throw to monkey 0
+    If false: throw to monkey 2
+
+Monkey 2:
+  Starting items: 13
+  Operation: new = old * 11 + 1
+  Test: divisible by 7
+    If true: throw to monkey 0
+    If false: throw to monkey 1";
+        let mut reduced = MonkeyBusiness::<BigInt>::with_relief(s, Relief::None);
+        let mut unreduced = MonkeyBusiness::unreduced(s, 1);
+
+        assert_eq!(reduced.common_divider, None);
+        assert_eq!(MonkeyBusiness::new(s, 1).common_divider, None);
+        assert_eq!(reduced.run(20), unreduced.run(20));
+        assert_eq!(reduced.counts, unreduced.counts);
+        assert_eq!(
+            MonkeyBusiness::<BigInt>::with_relief(s, Relief::None)
+                .extrapolate(20)
+                .unwrap(),
+            unreduced
+                .counts
+                .iter()
+                .map(|&c| c as u64)
+                .collect::<Vec<_>>()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_overflow_is_reported() -> Result<(), String> {
+        let s = "Monkey 0:
+  Starting items: 4000000000
+  Operation: new = old * old
+  Test: divisible by 1000003
+    If true: throw to monkey 1
+    If false: throw to monkey 1
+
+Monkey 1:
+  Starting items: 1
+  Operation: new = old + 1
+  Test: divisible by 2000003
+    If true: throw to monkey 0
+    If false: throw to monkey 0";
+        let mut business = MonkeyBusiness::new(s, 1);
+
+        assert_eq!(
+            business.run(1),
+            Err(MonkeyError {
+                monkey: 0,
+                item: String::from("4000000000"),
+                error: ArithmeticError::Overflow,
+            })
+        );
+
+        let mut business = MonkeyBusiness::unreduced(s, 1);
+        assert_eq!(business.run(1), Ok(2));
+        Ok(())
+    }
+
+    #[test]
+    fn test_relief_policies() -> Result<(), String> {
+        let s = fs::read_to_string("test_input.txt").expect("File not found");
+
+        let mut divide = MonkeyBusiness::<i64>::with_relief(&s, Relief::Divide(3));
+        assert_eq!(divide.run(20), Ok(10605));
+
+        let mut none = MonkeyBusiness::<i64>::with_relief(&s, Relief::None);
+        assert_eq!(none.run(10000), Ok(2713310158));
+
+        let custom = Relief::Custom("old / 3".parse()?);
+        let mut custom = MonkeyBusiness::<i64>::with_relief(&s, custom);
+        assert_eq!(custom.run(20), Ok(10605));
+
+        let mut modulo = MonkeyBusiness::<i64>::with_relief(&s, Relief::Modulo(96577));
+        assert_eq!(modulo.run(10000), Ok(2713310158));
+        Ok(())
+    }
+
+    #[test]
+    fn test_reduction_follows_relief() -> Result<(), String> {
+        let s = fs::read_to_string("test_input.txt").expect("File not found");
+        let divider = |relief| MonkeyBusiness::<i64>::with_relief(&s, relief).common_divider;
+
+        assert_eq!(divider(Relief::None), Some(96577));
+        assert_eq!(divider(Relief::Divide(1)), Some(96577));
+        assert_eq!(divider(Relief::Modulo(2 * 96577)), Some(96577));
+        assert_eq!(divider(Relief::Divide(3)), None);
+        assert_eq!(divider(Relief::Modulo(1000)), None);
+        assert_eq!(divider(Relief::Custom("old / 3".parse()?)), None);
+        assert_eq!(MonkeyBusiness::new(&s, 3).common_divider, None);
+        assert_eq!(MonkeyBusiness::new(&s, 1).common_divider, Some(96577));
+        Ok(())
+    }
+
+    #[test]
+    fn test_run_scored() -> Result<(), String> {
+        let s = fs::read_to_string("test_input.txt").expect("File not found");
+
+        let mut business = MonkeyBusiness::new(&s, 3);
+        assert_eq!(business.run_scored(20, &policy::Sum), Ok(308));
+
+        let mut business = MonkeyBusiness::new(&s, 3);
+        let report = business.run_scored(20, &policy::PerMonkey).unwrap();
+        assert_eq!(
+            report,
+            "Monkey 0 inspected items 101 times.
+Monkey 1 inspected items 95 times.
+Monkey 2 inspected items 7 times.
+Monkey 3 inspected items 105 times.
+"
+        );
+        Ok(())
+    }
+}
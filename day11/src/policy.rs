@@ -0,0 +1,107 @@
+use crate::{
+    expression::Expression,
+    worry::{ArithmeticError, Worry},
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Relief {
+    Divide(i64),
+    Modulo(i64),
+    None,
+    Custom(Expression),
+}
+
+impl Relief {
+    pub fn apply<W: Worry>(&self, item: W) -> Result<W, ArithmeticError> {
+        match self {
+            Self::Divide(0) | Self::Modulo(0) => Err(ArithmeticError::DivisionByZero),
+            Self::Divide(d) => Ok(item.relieve(*d)),
+            Self::Modulo(m) => Ok(item.reduce(*m)),
+            Self::None => Ok(item),
+            Self::Custom(e) => e.try_evaluate(&item),
+        }
+    }
+
+    /// Whether worry reduced modulo `divider` stays congruent under this
+    /// relief. Custom expressions are not inspected, so they never qualify.
+    pub fn keeps_remainder(&self, divider: i64) -> bool {
+        match self {
+            Self::None | Self::Divide(1) => true,
+            Self::Modulo(m) => *m != 0 && m % divider == 0,
+            Self::Divide(_) | Self::Custom(_) => false,
+        }
+    }
+}
+
+pub trait Scoring {
+    type Output;
+
+    fn score(&self, counts: &[usize]) -> Self::Output;
+}
+
+pub struct TopProduct(pub usize);
+
+impl Scoring for TopProduct {
+    type Output = usize;
+
+    fn score(&self, counts: &[usize]) -> usize {
+        let mut sorted = counts.to_vec();
+        sorted.sort_by(|a, b| b.cmp(a));
+        sorted.iter().take(self.0).product()
+    }
+}
+
+pub struct Sum;
+
+impl Scoring for Sum {
+    type Output = usize;
+
+    fn score(&self, counts: &[usize]) -> usize {
+        counts.iter().sum()
+    }
+}
+
+pub struct PerMonkey;
+
+impl Scoring for PerMonkey {
+    type Output = String;
+
+    fn score(&self, counts: &[usize]) -> String {
+        counts
+            .iter()
+            .enumerate()
+            .map(|(i, c)| format!("Monkey {} inspected items {} times.\n", i, c))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_relief() -> Result<(), String> {
+        assert_eq!(Relief::Divide(3).apply(1501_i64), Ok(500));
+        assert_eq!(Relief::Modulo(7).apply(20_i64), Ok(6));
+        assert_eq!(Relief::None.apply(20_i64), Ok(20));
+        assert_eq!(Relief::Custom("old - 1".parse()?).apply(20_i64), Ok(19));
+        assert_eq!(
+            Relief::Divide(0).apply(20_i64),
+            Err(ArithmeticError::DivisionByZero)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_scoring() -> Result<(), String> {
+        let counts = [101, 95, 7, 105];
+        assert_eq!(TopProduct(2).score(&counts), 10605);
+        assert_eq!(TopProduct(3).score(&counts), 1007475);
+        assert_eq!(Sum.score(&counts), 308);
+        assert!(PerMonkey
+            .score(&counts)
+            .starts_with("Monkey 0 inspected items 101 times.\nMonkey 1"));
+        Ok(())
+    }
+}
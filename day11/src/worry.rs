@@ -0,0 +1,123 @@
+use std::{
+    fmt::{Debug, Display},
+    hash::Hash,
+};
+
+use num_bigint::BigInt;
+
+use crate::expression::Operator;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ArithmeticError {
+    Overflow,
+    DivisionByZero,
+}
+
+pub trait Worry: Clone + Debug + Display + PartialEq + Eq + Hash {
+    fn from_i64(v: i64) -> Self;
+    fn combine(op: Operator, a: &Self, b: &Self) -> Result<Self, ArithmeticError>;
+    fn relieve(&self, divisor: i64) -> Self;
+    fn is_divisible_by(&self, test: i64) -> bool;
+    fn reduce(&self, modulus: i64) -> Self;
+}
+
+impl Worry for i64 {
+    fn from_i64(v: i64) -> Self {
+        v
+    }
+
+    fn combine(op: Operator, a: &Self, b: &Self) -> Result<Self, ArithmeticError> {
+        let result = match op {
+            Operator::Add => a.checked_add(*b),
+            Operator::Subtract => a.checked_sub(*b),
+            Operator::Multiply => a.checked_mul(*b),
+            Operator::Divide if *b == 0 => return Err(ArithmeticError::DivisionByZero),
+            Operator::Divide => a.checked_div(*b),
+        };
+        result.ok_or(ArithmeticError::Overflow)
+    }
+
+    fn relieve(&self, divisor: i64) -> Self {
+        self / divisor
+    }
+
+    fn is_divisible_by(&self, test: i64) -> bool {
+        self % test == 0
+    }
+
+    fn reduce(&self, modulus: i64) -> Self {
+        self % modulus
+    }
+}
+
+impl Worry for BigInt {
+    fn from_i64(v: i64) -> Self {
+        BigInt::from(v)
+    }
+
+    fn combine(op: Operator, a: &Self, b: &Self) -> Result<Self, ArithmeticError> {
+        match op {
+            Operator::Add => Ok(a + b),
+            Operator::Subtract => Ok(a - b),
+            Operator::Multiply => Ok(a * b),
+            Operator::Divide if *b == BigInt::from(0) => Err(ArithmeticError::DivisionByZero),
+            Operator::Divide => Ok(a / b),
+        }
+    }
+
+    fn relieve(&self, divisor: i64) -> Self {
+        self / divisor
+    }
+
+    fn is_divisible_by(&self, test: i64) -> bool {
+        self % test == BigInt::from(0)
+    }
+
+    fn reduce(&self, modulus: i64) -> Self {
+        self % modulus
+    }
+}
+
+fn gcd(a: i64, b: i64) -> i64 {
+    match b {
+        0 => a,
+        _ => gcd(b, a % b),
+    }
+}
+
+pub fn lcm(mut values: impl Iterator<Item = i64>) -> Option<i64> {
+    values.try_fold(1, |acc, v| (acc / gcd(acc, v)).checked_mul(v))
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_checked_combine() -> Result<(), String> {
+        assert_eq!(i64::combine(Operator::Multiply, &3, &4), Ok(12));
+        assert_eq!(
+            i64::combine(Operator::Multiply, &i64::MAX, &2),
+            Err(ArithmeticError::Overflow)
+        );
+        assert_eq!(
+            i64::combine(Operator::Divide, &3, &0),
+            Err(ArithmeticError::DivisionByZero)
+        );
+
+        let big = BigInt::from(i64::MAX);
+        let result = BigInt::combine(Operator::Multiply, &big, &BigInt::from(2)).unwrap();
+        assert_eq!(result.to_string(), "18446744073709551614");
+        assert!(result.is_divisible_by(2));
+        Ok(())
+    }
+
+    #[test]
+    fn test_lcm() -> Result<(), String> {
+        assert_eq!(lcm([4, 6, 10].into_iter()), Some(60));
+        assert_eq!(lcm([23, 19, 13, 17].into_iter()), Some(96577));
+        assert_eq!(lcm([i64::MAX, 2].into_iter()), None);
+        Ok(())
+    }
+}
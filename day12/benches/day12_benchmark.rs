@@ -1,7 +1,7 @@
 use std::fs;
 
 use criterion::{criterion_group, criterion_main, Criterion};
-use day12::{part1, part2};
+use day12::{part1, part2, Dijkstra, Rules, StepCost, Strategy};
 
 fn part1_benchmark(c: &mut Criterion) {
     let s = fs::read_to_string("input.txt").expect("File not found");
@@ -13,5 +13,27 @@ fn part2_benchmark(c: &mut Criterion) {
     c.bench_function("part 2", |b| b.iter(|| part2(&s)));
 }
 
-criterion_group!(benches, part1_benchmark, part2_benchmark);
+fn strategy_benchmark(c: &mut Criterion) {
+    let s = fs::read_to_string("input.txt").expect("File not found");
+    let weighted = Rules {
+        cost: StepCost::Custom(|climb| 1 + climb.unsigned_abs()),
+        ..Rules::default()
+    };
+    let mut group = c.benchmark_group("strategies");
+    for (name, rules) in [("unit", Rules::default()), ("weighted", weighted)] {
+        for strategy in [Strategy::Dijkstra, Strategy::AStar] {
+            group.bench_function(format!("{:?} {}", strategy, name), |b| {
+                b.iter(|| Dijkstra::with_strategy(&s, rules, strategy).run())
+            });
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    part1_benchmark,
+    part2_benchmark,
+    strategy_benchmark
+);
 criterion_main!(benches);
@@ -1,21 +1,125 @@
-use std::collections::HashSet;
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, VecDeque};
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct Path {
+    pub coords: Vec<(u32, u32)>,
+    pub cost: u32,
+}
+
+impl Path {
+    pub fn steps(&self) -> u32 {
+        self.coords.len() as u32 - 1
+    }
+}
+
+/// What a single move costs, given the height difference (destination minus
+/// origin) it climbs.
+#[derive(Debug, Clone, Copy)]
+pub enum StepCost {
+    Unit,
+    Custom(fn(i32) -> u32),
+}
+
+impl StepCost {
+    fn of(&self, climb: i32) -> u32 {
+        match self {
+            StepCost::Unit => 1,
+            StepCost::Custom(f) => f(climb),
+        }
+    }
+}
+
+/// Which moves are allowed between neighbouring cells. `None` means there is
+/// no limit in that direction.
+#[derive(Debug, Clone, Copy)]
+pub struct Rules {
+    pub max_ascent: Option<u32>,
+    pub max_descent: Option<u32>,
+    pub diagonal: bool,
+    pub cost: StepCost,
+}
+
+impl Default for Rules {
+    fn default() -> Self {
+        Self {
+            max_ascent: Some(1),
+            max_descent: None,
+            diagonal: false,
+            cost: StepCost::Unit,
+        }
+    }
+}
+
+impl Rules {
+    fn allows(&self, climb: i32) -> bool {
+        let within = |limit: Option<u32>, delta: i32| limit.is_none_or(|l| delta <= l as i32);
+        within(self.max_ascent, climb) && within(self.max_descent, -climb)
+    }
+
+    // Heights run from a to z, so no step can climb or drop more than 25.
+    fn cheapest_step(&self) -> u32 {
+        (-25..=25)
+            .filter(|&climb| self.allows(climb))
+            .map(|climb| self.cost.of(climb))
+            .min()
+            .unwrap_or(0)
+    }
+
+    fn offsets(&self) -> &'static [(i32, i32)] {
+        const STRAIGHT: [(i32, i32); 4] = [(1, 0), (-1, 0), (0, 1), (0, -1)];
+        const ALL: [(i32, i32); 8] = [
+            (1, 0),
+            (-1, 0),
+            (0, 1),
+            (0, -1),
+            (1, 1),
+            (1, -1),
+            (-1, 1),
+            (-1, -1),
+        ];
+        match self.diagonal {
+            false => &STRAIGHT,
+            true => &ALL,
+        }
+    }
+}
+
+/// How the shortest path is searched for. Both find the cheapest route;
+/// `AStar` steers towards the nearest target with a distance heuristic scaled
+/// by the cheapest allowed step, so free steps turn it back into Dijkstra.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Strategy {
+    #[default]
+    Dijkstra,
+    AStar,
+}
 
 #[derive(Debug)]
-struct Dijkstra {
+pub struct Dijkstra {
     height_grid: Vec<Vec<u32>>,
     grid: Vec<Vec<char>>,
     width: u32,
     height: u32,
     dist: Vec<u32>,
-    q: HashSet<u32>,
+    prev: Vec<u32>,
+    q: VecDeque<u32>,
     start_index: (u32, u32),
-    end_value: char,
-    reverse: bool,
+    rules: Rules,
+    strategy: Strategy,
 }
 
 impl Dijkstra {
-    fn new(s: &str) -> Self {
-        let grid: Vec<Vec<_>> = s.lines().map(|l| l.chars().map(|c| c).collect()).collect();
+    pub fn new(s: &str) -> Self {
+        Self::with_rules(s, Rules::default())
+    }
+
+    pub fn with_rules(s: &str, rules: Rules) -> Self {
+        Self::with_strategy(s, rules, Strategy::default())
+    }
+
+    pub fn with_strategy(s: &str, rules: Rules, strategy: Strategy) -> Self {
+        let grid: Vec<Vec<_>> = s.lines().map(|l| l.chars().collect()).collect();
         let height_grid: Vec<Vec<_>> = grid
             .iter()
             .map(|r| {
@@ -46,10 +150,11 @@ impl Dijkstra {
             width,
             height,
             dist: vec![u32::MAX; (width * height) as usize],
-            q: HashSet::new(),
+            prev: vec![u32::MAX; (width * height) as usize],
+            q: VecDeque::new(),
             start_index,
-            end_value: 'E',
-            reverse: false,
+            rules,
+            strategy,
         }
     }
 
@@ -63,124 +168,221 @@ impl Dijkstra {
         (x, y)
     }
 
-    fn initialise(&mut self) {
-        for y in 0..self.height {
-            for x in 0..self.width {
-                let index = self.get_index_value(x, y);
-                self.q.insert(index);
-            }
+    fn initialise(&mut self, sources: &[u32]) {
+        self.dist.fill(u32::MAX);
+        self.prev.fill(u32::MAX);
+        self.q.clear();
+        for &source in sources {
+            self.dist[source as usize] = 0;
+            self.q.push_back(source);
         }
-        let (start_x, start_y) = self.start_index;
-        let start_index = self.get_index_value(start_x, start_y);
-        self.dist[start_index as usize] = 0;
     }
 
-    fn run(&mut self) -> u32 {
-        self.initialise();
-        while !self.q.is_empty() {
-            let next_index = self.get_next_vertex();
+    /// Shortest route from 'S' to 'E'.
+    pub fn run(&mut self) -> Option<Path> {
+        let targets = self.positions_of('E');
+        self.shortest_path(&[self.start_index], &targets)
+    }
 
-            let (x, y) = self.get_coords_from_value(next_index);
-            if self.grid[y as usize][x as usize] == self.end_value {
-                return self.dist[next_index as usize];
+    /// Shortest route starting at any of `sources` and ending at any of
+    /// `targets`.
+    pub fn shortest_path(
+        &mut self,
+        sources: &[(u32, u32)],
+        targets: &[(u32, u32)],
+    ) -> Option<Path> {
+        let sources: Vec<u32> = sources
+            .iter()
+            .map(|&(x, y)| self.get_index_value(x, y))
+            .collect();
+        let mut is_target = vec![false; self.dist.len()];
+        for &(x, y) in targets {
+            is_target[self.get_index_value(x, y) as usize] = true;
+        }
+        self.initialise(&sources);
+        let end = match (self.strategy, self.rules.cost) {
+            (Strategy::Dijkstra, StepCost::Unit) => self.breadth_first(&is_target),
+            (Strategy::Dijkstra, StepCost::Custom(_)) => {
+                self.cheapest_first(&sources, &is_target, |_| 0)
             }
-            self.q.remove(&next_index);
-            let neighbours = match self.reverse {
-                false => self.get_valid_neighbours(next_index),
-                true => self.get_valid_neighbours_reverse(next_index),
-            };
-            for neighbour in neighbours.iter() {
-                let new_dist = self.dist[next_index as usize] + 1;
-                if new_dist < self.dist[*neighbour as usize] {
-                    self.dist[*neighbour as usize] = new_dist;
+            (Strategy::AStar, _) => {
+                let targets: Vec<_> = targets.to_vec();
+                let diagonal = self.rules.diagonal;
+                let width = self.width;
+                let step = self.rules.cheapest_step();
+                self.cheapest_first(&sources, &is_target, |index| {
+                    let (x, y) = (index % width, index / width);
+                    targets
+                        .iter()
+                        .map(|&(t_x, t_y)| distance((x, y), (t_x, t_y), diagonal))
+                        .min()
+                        .unwrap_or(0)
+                        .saturating_mul(step)
+                })
+            }
+        }?;
+        Some(self.build_path(end))
+    }
+
+    // Every step costs 1, so a plain breadth first search visits cells in order
+    // of distance and the first time we reach a target is the shortest path.
+    fn breadth_first(&mut self, is_target: &[bool]) -> Option<u32> {
+        while let Some(next_index) = self.get_next_vertex() {
+            if is_target[next_index as usize] {
+                return Some(next_index);
+            }
+            let new_dist = self.dist[next_index as usize] + 1;
+            for (neighbour, _) in self.get_valid_neighbours(next_index) {
+                if self.dist[neighbour as usize] == u32::MAX {
+                    self.dist[neighbour as usize] = new_dist;
+                    self.prev[neighbour as usize] = next_index;
+                    self.q.push_back(neighbour);
                 }
             }
         }
-        unreachable!();
+        None
     }
 
-    fn get_valid_neighbours(&self, index: u32) -> Vec<u32> {
-        let (x, y) = self.get_coords_from_value(index);
-        let value = self.height_grid[y as usize][x as usize];
-        let to_check: Vec<(i32, i32)> = vec![(1, 0), (-1, 0), (0, 1), (0, -1)];
-        let mut result: Vec<u32> = Vec::new();
-
-        for (i, j) in to_check.iter() {
-            let n_x = x as i32 + i;
-            let n_y = y as i32 + j;
-            if n_x < 0 || n_x as u32 >= self.width || n_y < 0 || n_y as u32 >= self.height {
+    // Expands cells in order of cost so far plus the `estimate` of what is
+    // left. With an estimate of 0 this is Dijkstra's algorithm, otherwise A*.
+    fn cheapest_first(
+        &mut self,
+        sources: &[u32],
+        is_target: &[bool],
+        estimate: impl Fn(u32) -> u32,
+    ) -> Option<u32> {
+        let mut heap: BinaryHeap<_> = sources
+            .iter()
+            .map(|&s| Reverse((estimate(s), 0, s)))
+            .collect();
+        while let Some(Reverse((_, dist, index))) = heap.pop() {
+            if dist > self.dist[index as usize] {
                 continue;
             }
-            if value + 1 < self.height_grid[n_y as usize][n_x as usize] {
-                continue;
+            if is_target[index as usize] {
+                return Some(index);
             }
-            let new_index = self.get_index_value(n_x as u32, n_y as u32);
-            if self.q.contains(&new_index) {
-                result.push(new_index);
+            for (neighbour, cost) in self.get_valid_neighbours(index) {
+                let new_dist = dist.saturating_add(cost);
+                if new_dist < self.dist[neighbour as usize] {
+                    self.dist[neighbour as usize] = new_dist;
+                    self.prev[neighbour as usize] = index;
+                    let guess = new_dist.saturating_add(estimate(neighbour));
+                    heap.push(Reverse((guess, new_dist, neighbour)));
+                }
             }
         }
-        result
+        None
+    }
+
+    // Walking the predecessors gives the route from the end back to the source
+    // it was reached from, so flip it to read in travel order.
+    fn build_path(&self, end: u32) -> Path {
+        let mut coords = vec![self.get_coords_from_value(end)];
+        let mut index = end;
+        while self.prev[index as usize] != u32::MAX {
+            index = self.prev[index as usize];
+            coords.push(self.get_coords_from_value(index));
+        }
+        coords.reverse();
+        Path {
+            coords,
+            cost: self.dist[end as usize],
+        }
     }
 
-    fn get_valid_neighbours_reverse(&self, index: u32) -> Vec<u32> {
+    pub fn render(&self, path: &Path) -> String {
+        let mut canvas = vec![vec!['.'; self.width as usize]; self.height as usize];
+        for pair in path.coords.windows(2) {
+            let ((x1, y1), (x2, y2)) = (pair[0], pair[1]);
+            canvas[y1 as usize][x1 as usize] = match (x2 as i32 - x1 as i32, y2 as i32 - y1 as i32)
+            {
+                (1, 0) => '>',
+                (-1, 0) => '<',
+                (0, 1) => 'v',
+                (0, -1) => '^',
+                (1, 1) | (-1, -1) => '\\',
+                (1, -1) | (-1, 1) => '/',
+                _ => '?',
+            };
+        }
+        if let Some(&(x, y)) = path.coords.last() {
+            canvas[y as usize][x as usize] = self.grid[y as usize][x as usize];
+        }
+        canvas
+            .iter()
+            .map(|r| r.iter().collect::<String>() + "\n")
+            .collect()
+    }
+
+    // Moves out of `index` that the rules allow, with what each one costs.
+    fn get_valid_neighbours(&self, index: u32) -> Vec<(u32, u32)> {
         let (x, y) = self.get_coords_from_value(index);
-        let value = self.height_grid[y as usize][x as usize];
-        let to_check: Vec<(i32, i32)> = vec![(1, 0), (-1, 0), (0, 1), (0, -1)];
-        let mut result: Vec<u32> = Vec::new();
+        let value = self.height_grid[y as usize][x as usize] as i32;
+        let mut result = Vec::new();
 
-        for (i, j) in to_check.iter() {
+        for (i, j) in self.rules.offsets() {
             let n_x = x as i32 + i;
             let n_y = y as i32 + j;
             if n_x < 0 || n_x as u32 >= self.width || n_y < 0 || n_y as u32 >= self.height {
                 continue;
             }
-            if value - 1 > self.height_grid[n_y as usize][n_x as usize] {
+            let climb = self.height_grid[n_y as usize][n_x as usize] as i32 - value;
+            if !self.rules.allows(climb) {
                 continue;
             }
             let new_index = self.get_index_value(n_x as u32, n_y as u32);
-            if self.q.contains(&new_index) {
-                result.push(new_index);
-            }
+            result.push((new_index, self.rules.cost.of(climb)));
         }
         result
     }
 
-    fn get_next_vertex(&self) -> u32 {
-        let (min_element, _) = self
-            .q
-            .iter()
-            .map(|i| (*i, self.dist[*i as usize]))
-            .min_by_key(|(_, d)| *d)
-            .unwrap();
-        min_element
+    fn get_next_vertex(&mut self) -> Option<u32> {
+        self.q.pop_front()
     }
 
-    fn get_index_of(&self, c: char) -> (u32, u32) {
+    pub fn positions_of(&self, c: char) -> Vec<(u32, u32)> {
+        let mut result = Vec::new();
         for y in 0..self.height {
             for x in 0..self.width {
                 if self.grid[y as usize][x as usize] == c {
-                    return (x, y);
+                    result.push((x, y));
                 }
             }
         }
-        unreachable!();
+        result
+    }
+
+    /// Every cell at the lowest elevation, 'S' included.
+    pub fn lowest_points(&self) -> Vec<(u32, u32)> {
+        let mut result = self.positions_of('a');
+        result.extend(self.positions_of('S'));
+        result
+    }
+}
+
+// Fewest moves between two cells: Manhattan distance on a four way grid, or
+// Chebyshev distance when diagonal moves are allowed.
+fn distance(a: (u32, u32), b: (u32, u32), diagonal: bool) -> u32 {
+    let (dx, dy) = (a.0.abs_diff(b.0), a.1.abs_diff(b.1));
+    match diagonal {
+        false => dx + dy,
+        true => dx.max(dy),
     }
 }
 
 pub fn part1(s: &str) -> u32 {
     let mut d = Dijkstra::new(s);
-    let result = d.run();
-    result
+    d.run().expect("No route to the summit").steps()
 }
 
 pub fn part2(s: &str) -> u32 {
-    let mut d = Dijkstra::new(&s);
-    d.end_value = 'a';
-    d.start_index = d.get_index_of('E');
-    d.reverse = true;
-
-    let result = d.run();
-    result
+    let mut d = Dijkstra::new(s);
+    let sources = d.lowest_points();
+    let targets = d.positions_of('E');
+    d.shortest_path(&sources, &targets)
+        .expect("No route to the summit")
+        .steps()
 }
 
 #[cfg(test)]
@@ -215,8 +417,8 @@ mod tests {
         let s = fs::read_to_string("test_input.txt").expect("File not found");
 
         let mut d = Dijkstra::new(&s);
-        let result = d.run();
-        assert_eq!(result, 31);
+        let result = d.run().unwrap();
+        assert_eq!(result.steps(), 31);
         Ok(())
     }
 
@@ -244,9 +446,10 @@ mod tests {
         let s = fs::read_to_string("test_input.txt").expect("File not found");
 
         let mut d = Dijkstra::new(&s);
-        d.initialise();
+        d.initialise(&[0]);
 
-        assert_eq!(d.get_next_vertex(), 0);
+        assert_eq!(d.get_next_vertex(), Some(0));
+        assert_eq!(d.get_next_vertex(), None);
         Ok(())
     }
 
@@ -255,12 +458,195 @@ mod tests {
         let s = fs::read_to_string("test_input.txt").expect("File not found");
 
         let mut d = Dijkstra::new(&s);
-        let start_index = d.get_index_of('E');
-        d.start_index = start_index;
-        d.end_value = 'a';
-        d.reverse = true;
-        let result = d.run();
-        assert_eq!(result, 29);
+        let sources = d.lowest_points();
+        let targets = d.positions_of('E');
+        let result = d.shortest_path(&sources, &targets).unwrap();
+        assert_eq!(result.steps(), 29);
+        assert_eq!(result.coords[0], (0, 4));
+        Ok(())
+    }
+
+    #[test]
+    fn test_render_path() -> Result<(), String> {
+        let s = fs::read_to_string("test_input.txt").expect("File not found");
+
+        let mut d = Dijkstra::new(&s);
+        let path = d.run().unwrap();
+        assert_eq!(path.coords[0], (0, 0));
+        assert_eq!(*path.coords.last().unwrap(), (5, 2));
+
+        assert_eq!(
+            d.render(&path),
+            "\
+>>vv<<<<
+..vvv<<^
+..vv>E^^
+..v>>>^^
+..>>>>>^
+"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_unreachable() -> Result<(), String> {
+        let mut d = Dijkstra::new("Sac\nccE");
+        assert_eq!(d.run(), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_rules() -> Result<(), String> {
+        let mut d = Dijkstra::new("abcb");
+        assert_eq!(d.shortest_path(&[(0, 0)], &[(3, 0)]).unwrap().steps(), 3);
+        let no_descent = Rules {
+            max_descent: Some(0),
+            ..Rules::default()
+        };
+        let mut d = Dijkstra::with_rules("abcb", no_descent);
+        assert_eq!(d.shortest_path(&[(0, 0)], &[(3, 0)]), None);
+
+        let mut d = Dijkstra::new("acea");
+        assert_eq!(d.shortest_path(&[(0, 0)], &[(3, 0)]), None);
+        let long_legs = Rules {
+            max_ascent: Some(2),
+            ..Rules::default()
+        };
+        let mut d = Dijkstra::with_rules("acea", long_legs);
+        assert_eq!(d.shortest_path(&[(0, 0)], &[(3, 0)]).unwrap().steps(), 3);
+        Ok(())
+    }
+
+    #[test]
+    fn test_diagonal() -> Result<(), String> {
+        let diagonal = Rules {
+            diagonal: true,
+            ..Rules::default()
+        };
+        let mut d = Dijkstra::with_rules("ab\nbb", diagonal);
+        let path = d.shortest_path(&[(0, 0)], &[(1, 1)]).unwrap();
+        assert_eq!(path.steps(), 1);
+        assert_eq!(d.render(&path), "\\.\n.b\n");
+
+        let s = fs::read_to_string("test_input.txt").expect("File not found");
+        let mut d = Dijkstra::with_rules(&s, diagonal);
+        assert!(d.run().unwrap().steps() < 31);
+        Ok(())
+    }
+
+    #[test]
+    fn test_weighted_cost() -> Result<(), String> {
+        let mut d = Dijkstra::new("aba\naaa");
+        assert_eq!(d.shortest_path(&[(0, 0)], &[(2, 0)]).unwrap().steps(), 2);
+
+        // Climbing costs ten, so going round the bump beats going over it.
+        let rules = Rules {
+            cost: StepCost::Custom(|climb| if climb > 0 { 10 } else { 1 }),
+            ..Rules::default()
+        };
+        let mut d = Dijkstra::with_rules("aba\naaa", rules);
+        let path = d.shortest_path(&[(0, 0)], &[(2, 0)]).unwrap();
+        assert_eq!(path.steps(), 4);
+        assert_eq!(path.cost, 4);
+        assert_eq!(d.render(&path), "v.a\n>>^\n");
+        Ok(())
+    }
+
+    #[test]
+    fn test_astar() -> Result<(), String> {
+        let s = fs::read_to_string("test_input.txt").expect("File not found");
+        let weighted = Rules {
+            cost: StepCost::Custom(|climb| 1 + climb.unsigned_abs()),
+            ..Rules::default()
+        };
+        for rules in [Rules::default(), weighted] {
+            let mut d = Dijkstra::with_rules(&s, rules);
+            let expected = d.run().unwrap();
+            let mut d = Dijkstra::with_strategy(&s, rules, Strategy::AStar);
+            let path = d.run().unwrap();
+            assert_eq!(path.cost, expected.cost);
+            assert_eq!(path.steps(), expected.steps());
+
+            let sources = d.lowest_points();
+            let targets = d.positions_of('E');
+            let mut reference = Dijkstra::with_rules(&s, rules);
+            assert_eq!(
+                d.shortest_path(&sources, &targets).unwrap().cost,
+                reference.shortest_path(&sources, &targets).unwrap().cost
+            );
+        }
+
+        let mut d = Dijkstra::with_strategy("Sac\nccE", Rules::default(), Strategy::AStar);
+        assert_eq!(d.run(), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_astar_free_descents() -> Result<(), String> {
+        // Going down costs nothing, so the distance to the target says nothing
+        // about the cost left and A* must not lean on it.
+        let rules = Rules {
+            max_ascent: None,
+            cost: StepCost::Custom(|climb| if climb < 0 { 0 } else { 1 }),
+            ..Rules::default()
+        };
+        let mut seed: u64 = 12;
+        let mut random = || {
+            seed = seed
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (seed >> 33) as u8 % 26
+        };
+        for _ in 0..200 {
+            let mut s: String = (0..7)
+                .map(|_| {
+                    (0..9)
+                        .map(|_| (b'a' + random()) as char)
+                        .collect::<String>()
+                        + "\n"
+                })
+                .collect();
+            s.replace_range(0..1, "S");
+            s.replace_range(s.len() - 2..s.len() - 1, "E");
+
+            let expected = Dijkstra::with_rules(&s, rules).run().unwrap();
+            let path = Dijkstra::with_strategy(&s, rules, Strategy::AStar)
+                .run()
+                .unwrap();
+            assert_eq!(path.cost, expected.cost, "{}", s);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_multi_source() -> Result<(), String> {
+        let mut d = Dijkstra::new("Sbcdc\nabcEb");
+        let path = d
+            .shortest_path(&d.lowest_points(), &d.positions_of('c'))
+            .unwrap();
+        assert_eq!(path.steps(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_large_map() -> Result<(), String> {
+        let size = 1000;
+        let ramp = "bcdefghijklmnopqrstuvwxyzE";
+        let mut s = String::new();
+        for y in 0..size {
+            let mut line = "a".repeat(size);
+            if y == 0 {
+                line.replace_range(0..1, "S");
+            }
+            if y == size - 1 {
+                line.replace_range(size - ramp.len().., ramp);
+            }
+            s.push_str(&line);
+            s.push('\n');
+        }
+
+        assert_eq!(part1(&s), 2 * (size as u32 - 1));
+        assert_eq!(part2(&s), 26);
         Ok(())
     }
 }
@@ -0,0 +1,77 @@
+use rand::Rng;
+
+use crate::packet::Packet;
+
+/// Builds random packets. Small integers and short lists are the default so
+/// that generated packets often share prefixes and exercise the tie breaks.
+#[derive(Debug, Clone, Copy)]
+pub struct Generator {
+    pub max_depth: usize,
+    pub max_len: usize,
+    pub max_value: u64,
+}
+
+impl Default for Generator {
+    fn default() -> Self {
+        Self {
+            max_depth: 4,
+            max_len: 4,
+            max_value: 10,
+        }
+    }
+}
+
+impl Generator {
+    /// A random list packet, as found on each line of the puzzle input.
+    pub fn packet<R: Rng>(&self, rng: &mut R) -> Packet {
+        self.list(rng, self.max_depth)
+    }
+
+    fn list<R: Rng>(&self, rng: &mut R, depth: usize) -> Packet {
+        let len = rng.gen_range(0..=self.max_len);
+        Packet::List((0..len).map(|_| self.item(rng, depth)).collect())
+    }
+
+    fn item<R: Rng>(&self, rng: &mut R, depth: usize) -> Packet {
+        if depth == 0 || rng.gen_bool(0.6) {
+            Packet::Int(rng.gen_range(0..=self.max_value))
+        } else {
+            self.list(rng, depth - 1)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::*;
+
+    fn depth(packet: &Packet) -> usize {
+        match packet {
+            Packet::Int(_) => 0,
+            Packet::List(items) => 1 + items.iter().map(depth).max().unwrap_or(0),
+        }
+    }
+
+    #[test]
+    fn test_limits() -> Result<(), String> {
+        let generator = Generator {
+            max_depth: 2,
+            max_len: 3,
+            max_value: 5,
+        };
+        let mut rng = StdRng::seed_from_u64(13);
+        for _ in 0..200 {
+            let packet = generator.packet(&mut rng);
+            assert!(depth(&packet) <= 3);
+            assert!(packet
+                .to_string()
+                .split(['[', ']', ','])
+                .filter(|s| !s.is_empty())
+                .all(|n| n.parse::<u64>().unwrap() <= 5));
+        }
+        Ok(())
+    }
+}
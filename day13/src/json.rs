@@ -0,0 +1,103 @@
+use std::{error::Error, fmt};
+
+use serde_json::Value;
+
+use crate::packet::Packet;
+
+/// A JSON value with no packet equivalent: anything other than arrays and
+/// non-negative integers.
+#[derive(Debug, PartialEq, Eq)]
+pub struct JsonError {
+    pub value: Value,
+}
+
+impl fmt::Display for JsonError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} is not a packet", self.value)
+    }
+}
+
+impl Error for JsonError {}
+
+impl From<&Packet> for Value {
+    fn from(packet: &Packet) -> Self {
+        match packet {
+            Packet::Int(n) => Value::from(*n),
+            Packet::List(items) => Value::Array(items.iter().map(Value::from).collect()),
+        }
+    }
+}
+
+impl From<Packet> for Value {
+    fn from(packet: Packet) -> Self {
+        Value::from(&packet)
+    }
+}
+
+impl TryFrom<&Value> for Packet {
+    type Error = JsonError;
+
+    fn try_from(value: &Value) -> Result<Self, Self::Error> {
+        match value {
+            Value::Number(n) => n.as_u64().map(Packet::Int).ok_or_else(|| JsonError {
+                value: value.clone(),
+            }),
+            Value::Array(items) => items
+                .iter()
+                .map(Packet::try_from)
+                .collect::<Result<_, _>>()
+                .map(Packet::List),
+            _ => Err(JsonError {
+                value: value.clone(),
+            }),
+        }
+    }
+}
+
+impl TryFrom<Value> for Packet {
+    type Error = JsonError;
+
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        Packet::try_from(&value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_to_json() -> Result<(), String> {
+        let packet: Packet = "[1,[2,[]],30]".parse().unwrap();
+        assert_eq!(Value::from(&packet), json!([1, [2, []], 30]));
+        assert_eq!(Value::from(packet).to_string(), "[1,[2,[]],30]");
+        Ok(())
+    }
+
+    #[test]
+    fn test_from_json() -> Result<(), String> {
+        let value: Value = serde_json::from_str("[ [4, 4], 4, [] ]").unwrap();
+        let packet = Packet::try_from(value).unwrap();
+        assert!(packet.same_shape(&"[[4,4],4,[]]".parse().unwrap()));
+        assert!(!Packet::try_from(json!([[5]]))
+            .unwrap()
+            .same_shape(&Packet::Int(5)));
+        for bad in [
+            json!([1, -2]),
+            json!([1.5]),
+            json!(["a"]),
+            json!({}),
+            json!(null),
+        ] {
+            assert!(Packet::try_from(&bad).is_err());
+        }
+        assert_eq!(
+            Packet::try_from(json!([[true]])),
+            Err(JsonError { value: json!(true) })
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,221 @@
+use packet::Packet;
+
+pub mod generate;
+pub mod json;
+pub mod packet;
+pub mod trace;
+
+fn parse_packets(s: &str) -> impl Iterator<Item = Packet> + '_ {
+    s.lines()
+        .filter(|l| !l.is_empty())
+        .map(|l| l.parse().expect("Invalid packet"))
+}
+
+pub fn part1(s: &str) -> u32 {
+    let packets: Vec<Packet> = parse_packets(s).collect();
+    let mut result = 0;
+    for (i, pair) in packets.chunks(2).enumerate() {
+        if pair[0] < pair[1] {
+            result += (i as u32) + 1;
+        }
+    }
+    result
+}
+
+/// Walkthrough of every pair comparison, as in the puzzle description.
+pub fn explain(s: &str) -> String {
+    let packets: Vec<Packet> = parse_packets(s).collect();
+    packets
+        .chunks(2)
+        .enumerate()
+        .map(|(i, pair)| {
+            format!(
+                "== Pair {} ==\n{}",
+                i + 1,
+                trace::explain(&pair[0], &pair[1])
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+pub fn part2(s: &str) -> usize {
+    let dividers: [Packet; 2] = ["[[2]]".parse().unwrap(), "[[6]]".parse().unwrap()];
+    let mut packets: Vec<Packet> = parse_packets(s).collect();
+    packets.extend(dividers.iter().cloned());
+    packets.sort();
+    // Packets equal to a divider, such as `[2]`, may sort either side of it, so
+    // only the ones strictly smaller decide where it goes.
+    dividers
+        .iter()
+        .map(|d| packets.partition_point(|p| p < d) + 1)
+        .product()
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::{cmp::Ordering, fs};
+
+    use proptest::prelude::*;
+    use rand::{rngs::StdRng, SeedableRng};
+    use serde_json::Value;
+
+    use super::*;
+    use crate::generate::Generator;
+
+    #[test]
+    fn test_compare_lines() -> Result<(), String> {
+        let l1: Packet = "[[1],[2,3,4]]".parse().unwrap();
+        let l2: Packet = "[[1],4]".parse().unwrap();
+        assert!(l1 < l2);
+
+        let l1: Packet = "[[4,4],4,4]".parse().unwrap();
+        let l2: Packet = "[[4,4],4,4,4]".parse().unwrap();
+        assert!(l1 < l2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_part1() -> Result<(), String> {
+        let s = fs::read_to_string("test_input.txt").expect("File not found");
+        let result = part1(&s);
+        assert_eq!(result, 13);
+        Ok(())
+    }
+
+    #[test]
+    fn test_part2() -> Result<(), String> {
+        let s = fs::read_to_string("test_input.txt").expect("File not found");
+        let result = part2(&s);
+        assert_eq!(result, 140);
+        Ok(())
+    }
+
+    #[test]
+    fn test_explain() -> Result<(), String> {
+        let s = fs::read_to_string("test_input.txt").expect("File not found");
+        let result = explain(&s);
+        assert!(result.starts_with("== Pair 1 ==\n- Compare [1,1,3,1,1] vs [1,1,5,1,1]\n"));
+        assert!(result.contains(
+            "\
+== Pair 4 ==
+- Compare [[4,4],4,4] vs [[4,4],4,4,4]
+  - Compare [4,4] vs [4,4]
+    - Compare 4 vs 4
+    - Compare 4 vs 4
+  - Compare 4 vs 4
+  - Compare 4 vs 4
+  - Left side ran out of items, so inputs are in the right order
+"
+        ));
+        assert_eq!(result.matches("== Pair").count(), 8);
+        Ok(())
+    }
+
+    fn packets() -> impl Strategy<Value = Packet> {
+        any::<u64>().prop_map(|seed| Generator::default().packet(&mut StdRng::seed_from_u64(seed)))
+    }
+
+    fn items(packet: Packet) -> Vec<Packet> {
+        match packet {
+            Packet::List(items) => items,
+            int => vec![int],
+        }
+    }
+
+    // Three packets sharing a random prefix and ending in short tails of small
+    // values, so they are often equal or only differ deep inside.
+    fn related() -> impl Strategy<Value = [Packet; 3]> {
+        any::<u64>().prop_map(|seed| {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let prefix = items(Generator::default().packet(&mut rng));
+            let tail = Generator {
+                max_depth: 2,
+                max_len: 2,
+                max_value: 1,
+            };
+            [(); 3].map(|_| Packet::List([prefix.clone(), items(tail.packet(&mut rng))].concat()))
+        })
+    }
+
+    fn wrap(packet: Packet, depth: usize) -> Packet {
+        (0..depth).fold(packet, |p, _| Packet::List(vec![p]))
+    }
+
+    proptest! {
+        #[test]
+        fn test_antisymmetric(a in packets(), b in packets()) {
+            prop_assert_eq!(a.cmp(&b), b.cmp(&a).reverse());
+            prop_assert_eq!(a.cmp(&a), Ordering::Equal);
+        }
+
+        #[test]
+        fn test_equal_shapes_are_interchangeable(n in 0..10u64, depth in 1..4usize, b in packets()) {
+            let int = Packet::Int(n);
+            let wrapped = wrap(int.clone(), depth);
+            prop_assert!(wrapped == int && !wrapped.same_shape(&int));
+            prop_assert_eq!(wrapped.cmp(&b), int.cmp(&b));
+            prop_assert_eq!(
+                Packet::List(vec![wrapped.clone(), b.clone()]).cmp(&Packet::List(vec![int.clone(), b.clone()])),
+                Ordering::Equal
+            );
+
+            // The sort is stable, so equal packets keep their order side by
+            // side, and dedup keeps only the first of them.
+            let mut tagged = [(&b, 'b'), (&wrapped, 'w'), (&int, 'i')];
+            tagged.sort_by(|x, y| x.0.cmp(y.0));
+            let order: String = tagged.iter().map(|(_, tag)| tag).collect();
+            prop_assert!(order.contains("wi"), "{}", order);
+            let mut sorted = vec![b.clone(), wrapped.clone(), int.clone()];
+            sorted.sort();
+            sorted.dedup();
+            prop_assert_eq!(sorted.len(), if b == int { 1 } else { 2 });
+            prop_assert!(sorted.iter().all(|p| !p.same_shape(&int)));
+        }
+
+        #[test]
+        fn test_transitive([a, b, c] in related()) {
+            let all = [&a, &b, &c];
+            for x in all {
+                for y in all {
+                    for z in all {
+                        if x <= y && y <= z {
+                            prop_assert!(x <= z);
+                        }
+                    }
+                }
+            }
+        }
+
+        #[test]
+        fn test_round_trip(a in packets()) {
+            // Equal packets may differ in shape, so check the shape is kept.
+            let printed = a.to_string();
+            prop_assert!(printed.parse::<Packet>().unwrap().same_shape(&a));
+            let value = Value::from(&a);
+            prop_assert_eq!(value.to_string(), printed);
+            prop_assert!(Packet::try_from(value).unwrap().same_shape(&a));
+        }
+
+        #[test]
+        fn test_trace_matches_ord(a in packets(), b in packets()) {
+            prop_assert_eq!(trace::explain(&a, &b).ordering, a.cmp(&b));
+        }
+    }
+
+    #[test]
+    fn test_divider_ties() -> Result<(), String> {
+        // `[2]` equals the first divider, which still lands right after `[1]`.
+        assert_eq!(part2("[2]\n[1]\n"), 2 * 4);
+        assert_eq!(part2("[1]\n[2]\n"), 2 * 4);
+        Ok(())
+    }
+
+    #[test]
+    fn test_large_integers() -> Result<(), String> {
+        let s = "[12]\n[3]\n\n[10,2]\n[10,11]\n";
+        assert_eq!(part1(s), 2);
+        Ok(())
+    }
+}
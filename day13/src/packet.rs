@@ -0,0 +1,247 @@
+use std::{cmp::Ordering, error::Error, fmt, iter::Peekable, str::CharIndices, str::FromStr};
+
+#[derive(Debug, Clone)]
+pub enum Packet {
+    Int(u64),
+    List(Vec<Packet>),
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ParseError {
+    UnexpectedChar(usize, char),
+    InvalidInteger(usize, String),
+    UnexpectedEnd,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::UnexpectedChar(i, c) => write!(f, "unexpected {:?} at {}", c, i),
+            Self::InvalidInteger(i, s) => write!(f, "invalid integer {:?} at {}", s, i),
+            Self::UnexpectedEnd => write!(f, "unexpected end of packet"),
+        }
+    }
+}
+
+impl Error for ParseError {}
+
+struct Parser<'a> {
+    s: &'a str,
+    chars: Peekable<CharIndices<'a>>,
+}
+
+impl<'a> Parser<'a> {
+    fn packet(&mut self) -> Result<Packet, ParseError> {
+        match self.chars.peek().copied() {
+            Some((_, '[')) => self.list(),
+            Some((i, c)) if c.is_ascii_digit() => self.int(i),
+            Some((i, c)) => Err(ParseError::UnexpectedChar(i, c)),
+            None => Err(ParseError::UnexpectedEnd),
+        }
+    }
+
+    fn list(&mut self) -> Result<Packet, ParseError> {
+        self.chars.next();
+        let mut items = Vec::new();
+        if let Some((_, ']')) = self.chars.peek() {
+            self.chars.next();
+            return Ok(Packet::List(items));
+        }
+        loop {
+            items.push(self.packet()?);
+            match self.chars.next() {
+                Some((_, ',')) => continue,
+                Some((_, ']')) => return Ok(Packet::List(items)),
+                Some((i, c)) => return Err(ParseError::UnexpectedChar(i, c)),
+                None => return Err(ParseError::UnexpectedEnd),
+            }
+        }
+    }
+
+    fn int(&mut self, start: usize) -> Result<Packet, ParseError> {
+        let mut end = start;
+        while let Some(&(i, c)) = self.chars.peek() {
+            if !c.is_ascii_digit() {
+                break;
+            }
+            end = i + 1;
+            self.chars.next();
+        }
+        let digits = &self.s[start..end];
+        digits
+            .parse()
+            .map(Packet::Int)
+            .map_err(|_| ParseError::InvalidInteger(start, digits.to_string()))
+    }
+}
+
+impl FromStr for Packet {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser {
+            s,
+            chars: s.char_indices().peekable(),
+        };
+        let packet = parser.packet()?;
+        match parser.chars.next() {
+            Some((i, c)) => Err(ParseError::UnexpectedChar(i, c)),
+            None => Ok(packet),
+        }
+    }
+}
+
+impl fmt::Display for Packet {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Int(n) => write!(f, "{}", n),
+            Self::List(items) => {
+                write!(f, "[")?;
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "{}", item)?;
+                }
+                write!(f, "]")
+            }
+        }
+    }
+}
+
+// Integers compare by value and lists item by item, the shorter list winning
+// a tie. A lone integer compared against a list is treated as a list of one.
+impl Ord for Packet {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (Self::Int(a), Self::Int(b)) => a.cmp(b),
+            (Self::List(a), Self::List(b)) => a.cmp(b),
+            (Self::Int(a), Self::List(b)) => [Self::Int(*a)][..].cmp(b),
+            (Self::List(a), Self::Int(b)) => a[..].cmp(&[Self::Int(*b)]),
+        }
+    }
+}
+
+impl Packet {
+    /// Structural equality, which unlike `==` tells `[[5]]` and `5` apart.
+    pub fn same_shape(&self, other: &Packet) -> bool {
+        match (self, other) {
+            (Self::Int(a), Self::Int(b)) => a == b,
+            (Self::List(a), Self::List(b)) => {
+                a.len() == b.len() && a.iter().zip(b).all(|(x, y)| x.same_shape(y))
+            }
+            _ => false,
+        }
+    }
+}
+
+// Equality follows the ordering, so `[[5]]` and `5` are equal even though they
+// are built differently. Use `same_shape` to tell them apart.
+impl PartialEq for Packet {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Packet {}
+
+impl PartialOrd for Packet {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn packet(s: &str) -> Packet {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_parse() -> Result<(), String> {
+        assert!(packet("[1,[23,[]],456]").same_shape(&Packet::List(vec![
+            Packet::Int(1),
+            Packet::List(vec![Packet::Int(23), Packet::List(vec![])]),
+            Packet::Int(456),
+        ])));
+        assert!(packet("7").same_shape(&Packet::Int(7)));
+        assert!(packet("[[7]]").same_shape(&Packet::List(vec![Packet::List(vec![Packet::Int(7)])])));
+        Ok(())
+    }
+
+    #[test]
+    fn test_same_shape() -> Result<(), String> {
+        assert!(packet("[1,[2]]").same_shape(&packet("[1,[2]]")));
+        assert!(!packet("[[5]]").same_shape(&packet("5")));
+        assert!(!packet("[1,[2]]").same_shape(&packet("[1,2]")));
+        assert!(!packet("[1]").same_shape(&packet("[1,[]]")));
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_errors() -> Result<(), String> {
+        assert_eq!("[1,2".parse::<Packet>(), Err(ParseError::UnexpectedEnd));
+        assert_eq!(
+            "[1;2]".parse::<Packet>(),
+            Err(ParseError::UnexpectedChar(2, ';'))
+        );
+        assert_eq!(
+            "[1,]".parse::<Packet>(),
+            Err(ParseError::UnexpectedChar(3, ']'))
+        );
+        assert_eq!(
+            "[1]]".parse::<Packet>(),
+            Err(ParseError::UnexpectedChar(3, ']'))
+        );
+        assert_eq!(
+            "[99999999999999999999]".parse::<Packet>(),
+            Err(ParseError::InvalidInteger(
+                1,
+                "99999999999999999999".to_string()
+            ))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_display() -> Result<(), String> {
+        for s in [
+            "[]",
+            "[[]]",
+            "[1,[2,[3,[4,[5,6,7]]]],8,9]",
+            "[10,[],[[11]]]",
+        ] {
+            assert_eq!(packet(s).to_string(), s);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_ordering() -> Result<(), String> {
+        assert!(packet("[1,1,3,1,1]") < packet("[1,1,5,1,1]"));
+        assert!(packet("[[1],[2,3,4]]") < packet("[[1],4]"));
+        assert!(packet("[9]") > packet("[[8,7,6]]"));
+        assert!(packet("[[4,4],4,4]") < packet("[[4,4],4,4,4]"));
+        assert!(packet("[7,7,7,7]") > packet("[7,7,7]"));
+        assert!(packet("[]") < packet("[3]"));
+        assert!(packet("[[[]]]") > packet("[[]]"));
+        assert!(packet("[1,[2,[3,[4,[5,6,7]]]],8,9]") > packet("[1,[2,[3,[4,[5,6,0]]]],8,9]"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_multi_digit() -> Result<(), String> {
+        // The old byte comparison only coped with 10.
+        assert!(packet("[9]") < packet("[10]"));
+        assert!(packet("[11]") > packet("[10]"));
+        assert!(packet("[2]") < packet("[12]"));
+        assert!(packet("[100]") > packet("[99]"));
+        assert_eq!(packet("[[5]]").cmp(&packet("5")), Ordering::Equal);
+        assert_eq!(packet("[[5]]"), packet("5"));
+        assert_ne!(packet("[[5]]").to_string(), packet("5").to_string());
+        Ok(())
+    }
+}
@@ -0,0 +1,272 @@
+use std::{cmp::Ordering, fmt};
+
+use crate::packet::Packet;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Side {
+    Left,
+    Right,
+}
+
+impl fmt::Display for Side {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Left => write!(f, "left"),
+            Self::Right => write!(f, "right"),
+        }
+    }
+}
+
+/// One decision taken while comparing two packets. `path` holds the list
+/// indices that lead from the outer packets down to the values compared.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum Step {
+    Compare {
+        depth: usize,
+        path: Vec<usize>,
+        left: Packet,
+        right: Packet,
+    },
+    Promote {
+        depth: usize,
+        side: Side,
+        value: u64,
+    },
+    Smaller {
+        depth: usize,
+        side: Side,
+    },
+    RanOut {
+        depth: usize,
+        side: Side,
+    },
+}
+
+impl fmt::Display for Step {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (depth, text) = match self {
+            Self::Compare {
+                depth, left, right, ..
+            } => (depth, format!("Compare {} vs {}", left, right)),
+            Self::Promote { depth, side, value } => (
+                depth,
+                format!(
+                    "Mixed types; convert {} to [{}] and retry comparison",
+                    side, value
+                ),
+            ),
+            Self::Smaller { depth, side } => (
+                depth,
+                format!(
+                    "{} side is smaller, so {}",
+                    capitalise(*side),
+                    verdict(*side)
+                ),
+            ),
+            Self::RanOut { depth, side } => (
+                depth,
+                format!(
+                    "{} side ran out of items, so {}",
+                    capitalise(*side),
+                    verdict(*side)
+                ),
+            ),
+        };
+        write!(f, "{}- {}", "  ".repeat(*depth), text)
+    }
+}
+
+fn capitalise(side: Side) -> &'static str {
+    match side {
+        Side::Left => "Left",
+        Side::Right => "Right",
+    }
+}
+
+fn verdict(side: Side) -> &'static str {
+    match side {
+        Side::Left => "inputs are in the right order",
+        Side::Right => "inputs are not in the right order",
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Trace {
+    pub steps: Vec<Step>,
+    pub ordering: Ordering,
+}
+
+// Prints the steps in the indented style of the puzzle walkthrough.
+impl fmt::Display for Trace {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for step in &self.steps {
+            writeln!(f, "{}", step)?;
+        }
+        Ok(())
+    }
+}
+
+/// Compares two packets the same way as `Ord`, recording every step taken.
+pub fn explain(left: &Packet, right: &Packet) -> Trace {
+    let mut steps = Vec::new();
+    let ordering = compare(left, right, 0, &mut Vec::new(), &mut steps);
+    Trace { steps, ordering }
+}
+
+fn compare(
+    left: &Packet,
+    right: &Packet,
+    depth: usize,
+    path: &mut Vec<usize>,
+    steps: &mut Vec<Step>,
+) -> Ordering {
+    steps.push(Step::Compare {
+        depth,
+        path: path.clone(),
+        left: left.clone(),
+        right: right.clone(),
+    });
+    match (left, right) {
+        (Packet::Int(a), Packet::Int(b)) => {
+            let ordering = a.cmp(b);
+            match ordering {
+                Ordering::Less => steps.push(Step::Smaller {
+                    depth: depth + 1,
+                    side: Side::Left,
+                }),
+                Ordering::Greater => steps.push(Step::Smaller {
+                    depth: depth + 1,
+                    side: Side::Right,
+                }),
+                Ordering::Equal => (),
+            }
+            ordering
+        }
+        (Packet::List(a), Packet::List(b)) => {
+            for i in 0.. {
+                match (a.get(i), b.get(i)) {
+                    (Some(l), Some(r)) => {
+                        path.push(i);
+                        let ordering = compare(l, r, depth + 1, path, steps);
+                        path.pop();
+                        if ordering != Ordering::Equal {
+                            return ordering;
+                        }
+                    }
+                    (None, Some(_)) => {
+                        steps.push(Step::RanOut {
+                            depth: depth + 1,
+                            side: Side::Left,
+                        });
+                        return Ordering::Less;
+                    }
+                    (Some(_), None) => {
+                        steps.push(Step::RanOut {
+                            depth: depth + 1,
+                            side: Side::Right,
+                        });
+                        return Ordering::Greater;
+                    }
+                    (None, None) => break,
+                }
+            }
+            Ordering::Equal
+        }
+        (Packet::Int(a), Packet::List(_)) => {
+            steps.push(Step::Promote {
+                depth: depth + 1,
+                side: Side::Left,
+                value: *a,
+            });
+            let promoted = Packet::List(vec![Packet::Int(*a)]);
+            compare(&promoted, right, depth + 1, path, steps)
+        }
+        (Packet::List(_), Packet::Int(b)) => {
+            steps.push(Step::Promote {
+                depth: depth + 1,
+                side: Side::Right,
+                value: *b,
+            });
+            let promoted = Packet::List(vec![Packet::Int(*b)]);
+            compare(left, &promoted, depth + 1, path, steps)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::fs;
+
+    use super::*;
+
+    fn trace(left: &str, right: &str) -> Trace {
+        explain(&left.parse().unwrap(), &right.parse().unwrap())
+    }
+
+    #[test]
+    fn test_walkthrough() -> Result<(), String> {
+        assert_eq!(
+            trace("[[1],[2,3,4]]", "[[1],4]").to_string(),
+            "\
+- Compare [[1],[2,3,4]] vs [[1],4]
+  - Compare [1] vs [1]
+    - Compare 1 vs 1
+  - Compare [2,3,4] vs 4
+    - Mixed types; convert right to [4] and retry comparison
+    - Compare [2,3,4] vs [4]
+      - Compare 2 vs 4
+        - Left side is smaller, so inputs are in the right order
+"
+        );
+        assert_eq!(
+            trace("[7,7,7,7]", "[7,7,7]").to_string(),
+            "\
+- Compare [7,7,7,7] vs [7,7,7]
+  - Compare 7 vs 7
+  - Compare 7 vs 7
+  - Compare 7 vs 7
+  - Right side ran out of items, so inputs are not in the right order
+"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_path() -> Result<(), String> {
+        let t = trace("[1,[2,[3,[4,[5,6,7]]]],8,9]", "[1,[2,[3,[4,[5,6,0]]]],8,9]");
+        let deepest = t
+            .steps
+            .iter()
+            .filter_map(|s| match s {
+                Step::Compare { path, .. } => Some(path.clone()),
+                _ => None,
+            })
+            .next_back();
+        assert_eq!(deepest, Some(vec![1, 1, 1, 1, 2]));
+        assert_eq!(
+            t.steps.last(),
+            Some(&Step::Smaller {
+                depth: 6,
+                side: Side::Right
+            })
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_agrees_with_ord() -> Result<(), String> {
+        let s = fs::read_to_string("test_input.txt").expect("File not found");
+        let packets: Vec<Packet> = s
+            .lines()
+            .filter(|l| !l.is_empty())
+            .map(|l| l.parse().unwrap())
+            .collect();
+        for left in &packets {
+            for right in &packets {
+                assert_eq!(explain(left, right).ordering, left.cmp(right));
+            }
+        }
+        Ok(())
+    }
+}
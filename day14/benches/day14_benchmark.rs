@@ -1,7 +1,7 @@
 use std::fs;
 
 use criterion::{criterion_group, criterion_main, Criterion};
-use day14::{part1, part2};
+use day14::{part1, part2, Cave};
 
 fn part1_benchmark(c: &mut Criterion) {
     let s = fs::read_to_string("input.txt").expect("File not found");
@@ -13,5 +13,17 @@ fn part2_benchmark(c: &mut Criterion) {
     c.bench_function("part 2", |b| b.iter(|| part2(&s)));
 }
 
-criterion_group!(benches, part1_benchmark, part2_benchmark);
+fn part2_simulated_benchmark(c: &mut Criterion) {
+    let s = fs::read_to_string("input.txt").expect("File not found");
+    c.bench_function("part 2 simulated", |b| {
+        b.iter(|| Cave::from_string(&s, true).fill())
+    });
+}
+
+criterion_group!(
+    benches,
+    part1_benchmark,
+    part2_benchmark,
+    part2_simulated_benchmark
+);
 criterion_main!(benches);
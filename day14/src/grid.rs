@@ -0,0 +1,97 @@
+use std::{
+    collections::HashMap,
+    hash::{BuildHasherDefault, Hasher},
+};
+
+const CHUNK: i32 = 64;
+
+// Chunk keys are small integer pairs, so a multiply-and-rotate hash is plenty
+// and far cheaper than the default SipHash on the hot lookup path.
+#[derive(Default)]
+struct ChunkHasher(u64);
+
+impl Hasher for ChunkHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &b in bytes {
+            self.write_u64(b as u64);
+        }
+    }
+
+    fn write_i32(&mut self, n: i32) {
+        self.write_u64(n as u32 as u64);
+    }
+
+    fn write_u64(&mut self, n: u64) {
+        self.0 = (self.0.rotate_left(5) ^ n).wrapping_mul(0x51_7c_c1_b7_27_22_0a_95);
+    }
+}
+
+/// Unbounded grid of tiles stored in square chunks that are only allocated
+/// once something is written to them. Cells never written read as `b'.'`.
+#[derive(Debug, Default, Clone)]
+pub struct SparseGrid {
+    chunks: HashMap<(i32, i32), Box<[u8]>, BuildHasherDefault<ChunkHasher>>,
+    bounds: Option<((i32, i32), (i32, i32))>,
+}
+
+fn split(x: i32, y: i32) -> ((i32, i32), usize) {
+    let key = (x.div_euclid(CHUNK), y.div_euclid(CHUNK));
+    let offset = y.rem_euclid(CHUNK) * CHUNK + x.rem_euclid(CHUNK);
+    (key, offset as usize)
+}
+
+impl SparseGrid {
+    pub fn get(&self, x: i32, y: i32) -> u8 {
+        let (key, offset) = split(x, y);
+        self.chunks.get(&key).map_or(b'.', |chunk| chunk[offset])
+    }
+
+    pub fn set(&mut self, x: i32, y: i32, value: u8) {
+        let (key, offset) = split(x, y);
+        let chunk = self
+            .chunks
+            .entry(key)
+            .or_insert_with(|| vec![b'.'; (CHUNK * CHUNK) as usize].into_boxed_slice());
+        chunk[offset] = value;
+        self.bounds = Some(match self.bounds {
+            None => ((x, y), (x, y)),
+            Some(((min_x, min_y), (max_x, max_y))) => {
+                ((min_x.min(x), min_y.min(y)), (max_x.max(x), max_y.max(y)))
+            }
+        });
+    }
+
+    /// Top left and bottom right corners of every cell written so far.
+    pub fn bounds(&self) -> Option<((i32, i32), (i32, i32))> {
+        self.bounds
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_get_set() -> Result<(), String> {
+        let mut grid = SparseGrid::default();
+        assert_eq!(grid.get(0, 0), b'.');
+        assert_eq!(grid.bounds(), None);
+
+        grid.set(-1, -1, b'#');
+        grid.set(63, 64, b'o');
+        grid.set(1_000_000, 5, b'#');
+        assert_eq!(grid.get(-1, -1), b'#');
+        assert_eq!(grid.get(63, 64), b'o');
+        assert_eq!(grid.get(1_000_000, 5), b'#');
+        assert_eq!(grid.get(0, 0), b'.');
+        assert_eq!(grid.get(-65, -1), b'.');
+        assert_eq!(grid.chunks.len(), 3);
+        assert_eq!(grid.bounds(), Some(((-1, -1), (1_000_000, 64))));
+        Ok(())
+    }
+}
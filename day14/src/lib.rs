@@ -1,251 +1,301 @@
-use std::cmp::Ordering;
+use grid::SparseGrid;
+use rules::Rules;
+
+pub mod grid;
+pub mod render;
+pub mod rules;
+
+/// Where sand pours in unless a cave is given its own source.
+pub const SAND_SOURCE: (i32, i32) = (500, 0);
 
 #[derive(Debug, PartialEq)]
 enum OutOfBoundsError {
-    Left,
-    Right,
     Bottom,
 }
 
-struct Cave {
-    grid: Vec<Vec<u8>>,
-    max_col: usize,
-    max_row: usize,
-    sand_drop_x: usize,
+pub struct Cave {
+    grid: SparseGrid,
+    max_row: i32,
+    sources: Vec<(i32, i32)>,
+    active: Vec<bool>,
+    turn: usize,
     infinite_width: bool,
+    rules: Rules,
 }
 
 #[derive(Debug)]
-struct Sand {
-    dropped: bool,
-    x: usize,
-    y: usize,
+pub struct Sand {
+    pub dropped: bool,
+    pub x: i32,
+    pub y: i32,
 }
 
 impl Sand {
-    fn new(dropped: bool, x: usize, y: usize) -> Self {
+    fn new(dropped: bool, x: i32, y: i32) -> Self {
         Self { dropped, x, y }
     }
 }
 
 impl Cave {
-    fn from_string(s: &str, infinite_width: bool) -> Self {
-        let mut max_row: usize = 0;
-        let mut min_col: usize = 500;
-        let mut max_col: usize = 500;
-        let paths: Vec<_> = s
-            .lines()
-            .map(|l| {
-                let coords: Vec<_> = l
-                    .split(" -> ")
-                    .map(|c| {
-                        let (x, y) = to_coords(c);
-                        if y > max_row {
-                            max_row = y;
-                        }
-                        if x > max_col {
-                            max_col = x;
-                        } else if x < min_col {
-                            min_col = x;
-                        }
-                        (x, y)
-                    })
-                    .collect();
-                coords
-            })
-            .collect();
+    pub fn from_string(s: &str, infinite_width: bool) -> Self {
+        Self::with_source(s, infinite_width, SAND_SOURCE)
+    }
 
-        max_row += match infinite_width {
-            true => 2,
-            false => 0,
-        };
+    // Rock is only stored where the scan lines put it, so the cave has no
+    // width limit. With `infinite_width` set the floor two rows below the
+    // lowest rock is implied by `get_grid_value` rather than stored.
+    pub fn with_source(s: &str, infinite_width: bool, source: (i32, i32)) -> Self {
+        Self::with_sources(s, infinite_width, &[source])
+    }
 
-        let mut grid = vec![vec![b'.'; max_row + 1]; max_col - min_col + 1];
-        for path in paths.iter() {
+    /// A cave fed from several sources, which take turns dropping a grain.
+    pub fn with_sources(s: &str, infinite_width: bool, sources: &[(i32, i32)]) -> Self {
+        let mut grid = SparseGrid::default();
+        let mut max_row = i32::MIN;
+        for line in s.lines().filter(|l| !l.is_empty()) {
+            let path: Vec<_> = line.split(" -> ").map(to_coords).collect();
+            max_row = path.iter().fold(max_row, |m, &(_, y)| m.max(y));
             for i in 1..path.len() {
                 let (mut x, mut y) = path[i - 1];
                 let (end_x, end_y) = path[i];
-                let dx = get_difference(x, end_x);
-                let dy = get_difference(y, end_y);
-                grid[end_x - min_col][end_y] = b'#';
+                let dx = (end_x - x).signum();
+                let dy = (end_y - y).signum();
+                grid.set(end_x, end_y, b'#');
                 while x != end_x || y != end_y {
-                    grid[x - min_col][y] = b'#';
-                    x = (x as isize + dx) as usize;
-                    y = (y as isize + dy) as usize;
+                    grid.set(x, y, b'#');
+                    x += dx;
+                    y += dy;
                 }
             }
         }
-
-        if infinite_width {
-            (0..grid.len()).for_each(|x| {
-                let len = grid[x].len();
-                grid[x][len - 1] = b'#';
-            });
+        if max_row == i32::MIN {
+            max_row = sources.iter().map(|&(_, y)| y).max().unwrap_or(0);
         }
-        let sand_drop_x = 500 - min_col;
-        max_col -= min_col;
+
+        max_row += match infinite_width {
+            true => 2,
+            false => 0,
+        };
 
         Self {
             grid,
-            max_col,
             max_row,
-            sand_drop_x,
+            sources: sources.to_vec(),
+            active: vec![true; sources.len()],
+            turn: 0,
             infinite_width,
+            rules: Rules::default(),
         }
     }
 
-    fn print_grid(&self) {
-        for y in 0..self.grid[0].len() {
-            for x in 0..self.grid.len() {
-                print!("{}", self.get_grid_value(x, y) as char)
-            }
-            println!();
+    /// Changes how grains move from the next one dropped on.
+    pub fn set_rules(&mut self, rules: Rules) {
+        self.rules = rules;
+    }
+
+    pub fn rules(&self) -> &Rules {
+        &self.rules
+    }
+
+    pub fn sources(&self) -> &[(i32, i32)] {
+        &self.sources
+    }
+
+    /// Top left and bottom right corners of everything placed so far, the
+    /// sources and, in floor mode, the floor beneath them.
+    pub fn bounds(&self) -> ((i32, i32), (i32, i32)) {
+        let (x, y) = self.sources.first().copied().unwrap_or(SAND_SOURCE);
+        let ((mut min_x, mut min_y), (mut max_x, mut max_y)) =
+            self.grid.bounds().unwrap_or(((x, y), (x, y)));
+        for &(x, y) in &self.sources {
+            min_x = min_x.min(x);
+            min_y = min_y.min(y);
+            max_x = max_x.max(x);
+            max_y = max_y.max(y);
+        }
+        if self.infinite_width {
+            max_y = max_y.max(self.max_row);
+        }
+        ((min_x, min_y), (max_x, max_y))
+    }
+
+    pub fn print_grid(&self) {
+        print!("{}", render::render(self, self.bounds(), &[]));
+    }
+
+    pub fn get_grid_value(&self, x: i32, y: i32) -> u8 {
+        match self.infinite_width && y >= self.max_row {
+            true => b'#',
+            false => self.grid.get(x, y),
         }
     }
+    fn set_grid_value(&mut self, x: i32, y: i32, value: u8) {
+        self.grid.set(x, y, value);
+    }
 
-    fn get_grid_value(&self, x: usize, y: usize) -> u8 {
-        self.grid[x][y]
+    /// Drops one grain from the next source in turn. It is not `dropped` if
+    /// that source is already buried or the grain falls into the abyss, and
+    /// the source is not used again.
+    pub fn drop_sand(&mut self) -> Sand {
+        self.drop_sand_with(|_| ())
     }
-    fn set_grid_value(&mut self, x: usize, y: usize, value: u8) {
-        self.grid[x][y] = value;
+
+    /// Like `drop_sand`, calling `step` with every position the grain passes
+    /// through, from the source to where it settles or leaves the cave.
+    pub fn drop_sand_with(&mut self, mut step: impl FnMut((i32, i32))) -> Sand {
+        let Some(turn) = (0..self.sources.len())
+            .map(|i| (self.turn + i) % self.sources.len())
+            .find(|&i| self.active[i])
+        else {
+            let (x, y) = self.sources.first().copied().unwrap_or(SAND_SOURCE);
+            return Sand::new(false, x, y);
+        };
+        self.turn = turn + 1;
+
+        let sand = self.fall(self.sources[turn], &mut step);
+        if !sand.dropped {
+            self.active[turn] = false;
+        }
+        sand
     }
 
-    fn drop_sand(&mut self) -> Sand {
-        let mut x = self.sand_drop_x;
-        let mut y: usize = 0;
-        let mut prev_x: usize = x;
-        let mut prev_y: usize = y;
+    fn fall(&mut self, (mut x, mut y): (i32, i32), step: &mut impl FnMut((i32, i32))) -> Sand {
+        if !self.is_empty(x, y) {
+            return Sand::new(false, x, y);
+        }
 
+        let mut flow = (0, 0);
         loop {
-            let next = self.next(x, y);
-            match next {
-                Err(OutOfBoundsError::Bottom) => return Sand::new(false, 0, 0),
-                Err(OutOfBoundsError::Left) => match self.infinite_width {
-                    false => return Sand::new(false, 0, 0),
-                    true => return self.increase_width(true),
-                },
-                Err(OutOfBoundsError::Right) => match self.infinite_width {
-                    false => return Sand::new(false, 0, 0),
-                    true => return self.increase_width(false),
+            step((x, y));
+            match self.next(x, y) {
+                Err(OutOfBoundsError::Bottom) => return Sand::new(false, x, y),
+                Ok(next) if next == (x, y) => match self.flow(x, y, &mut flow) {
+                    Some(next) => (x, y) = next,
+                    None => {
+                        self.set_grid_value(x, y, b'o');
+                        return Sand::new(true, x, y);
+                    }
                 },
-                Ok(_) => (x, y) = next.unwrap(),
+                Ok(next) => (x, y) = next,
             };
-            if x == prev_x && y == prev_y {
-                self.set_grid_value(x, y, b'o');
-                return Sand::new(true, x, y);
-            }
-            prev_x = x;
-            prev_y = y;
         }
     }
 
-    fn next(&self, x: usize, y: usize) -> Result<(usize, usize), OutOfBoundsError> {
-        match self.check_down(x, y) {
-            Ok(false) => {}
-            Ok(true) => return Ok((x, y + 1)),
-            Err(e) => return Err(e),
-        };
-        match self.check_left(x, y) {
-            Ok(false) => {}
-            Ok(true) => return Ok((x - 1, y + 1)),
-            Err(e) => return Err(e),
-        };
-        match self.check_right(x, y) {
-            Ok(false) => {}
-            Ok(true) => return Ok((x + 1, y + 1)),
-            Err(e) => return Err(e),
+    // Sideways step along flat ground for a grain that cannot fall, keeping
+    // the direction and number of steps taken so far in `flow`.
+    fn flow(&self, x: i32, y: i32, flow: &mut (i32, u32)) -> Option<(i32, i32)> {
+        let (direction, taken) = *flow;
+        if taken >= self.rules.flow() {
+            return None;
+        }
+        let directions = match direction {
+            0 => [-1, 1],
+            d => [d, d],
         };
-        Ok((x, y))
+        let d = directions.into_iter().find(|d| self.is_empty(x + d, y))?;
+        *flow = (d, taken + 1);
+        Some((x + d, y))
     }
 
-    fn check_down(&self, x: usize, y: usize) -> Result<bool, OutOfBoundsError> {
-        if self.max_row < y + 1 {
-            return Err(OutOfBoundsError::Bottom);
-        }
-        Ok(self.is_empty(x, y + 1))
+    /// Whether any source can still drop sand.
+    pub fn is_active(&self) -> bool {
+        self.active.contains(&true)
     }
 
-    fn check_left(&self, x: usize, y: usize) -> Result<bool, OutOfBoundsError> {
-        if x == 0 {
-            return Err(OutOfBoundsError::Left);
-        }
-        if self.max_row < y + 1 {
-            return Err(OutOfBoundsError::Bottom);
+    /// Drops grains until no source can add any more, returning how many
+    /// came to rest.
+    pub fn fill(&mut self) -> u32 {
+        let mut total = 0;
+        while self.is_active() {
+            if self.drop_sand().dropped {
+                total += 1;
+            }
         }
-        Ok(self.is_empty(x - 1, y + 1))
+        total
     }
 
-    fn check_right(&self, x: usize, y: usize) -> Result<bool, OutOfBoundsError> {
-        if x == self.max_col {
-            return Err(OutOfBoundsError::Right);
+    /// Settles every grain the floor will hold without dropping them one at a
+    /// time. Sand can reach a cell if it is not rock and one of the three cells
+    /// above it is reachable, so the pile grows a row at a time out from the
+    /// source. Without a floor grains are lost to the abyss, and other rules
+    /// or sources change the shape of the pile, so those fall back to `fill`.
+    pub fn settle(&mut self) -> u32 {
+        if !self.infinite_width || self.sources.len() != 1 || self.rules != Rules::default() {
+            return self.fill();
         }
-        if self.max_row < y + 1 {
-            return Err(OutOfBoundsError::Bottom);
+        self.active[0] = false;
+        let (source_x, source_y) = self.sources[0];
+        let mut reachable = vec![self.get_grid_value(source_x, source_y) != b'#'];
+        let mut total = self.settle_row(source_x, source_y, &reachable);
+        for y in source_y + 1..self.max_row {
+            if !reachable.contains(&true) {
+                break;
+            }
+            let left = source_x - (y - source_y);
+            reachable = (0..reachable.len() + 2)
+                .map(|i| {
+                    let above = &reachable[i.saturating_sub(2)..(i + 1).min(reachable.len())];
+                    above.contains(&true) && self.get_grid_value(left + i as i32, y) != b'#'
+                })
+                .collect();
+            total += self.settle_row(left, y, &reachable);
         }
-        Ok(self.is_empty(x + 1, y + 1))
+        total
     }
 
-    fn is_empty(&self, x: usize, y: usize) -> bool {
-        matches!(self.get_grid_value(x, y), b'.')
+    // Marks the reachable cells of row `y`, starting from column `left`, as
+    // sand and returns how many were still empty.
+    fn settle_row(&mut self, left: i32, y: i32, reachable: &[bool]) -> u32 {
+        let mut total = 0;
+        for (i, _) in reachable.iter().enumerate().filter(|(_, &r)| r) {
+            let x = left + i as i32;
+            if self.get_grid_value(x, y) == b'.' {
+                self.set_grid_value(x, y, b'o');
+                total += 1;
+            }
+        }
+        total
     }
 
-    fn increase_width(&mut self, left: bool) -> Sand {
-        let mut col = vec![b'.'; self.max_row + 1];
-        self.max_col += 1;
-        col[self.max_row - 1] = b'o';
-        col[self.max_row] = b'#';
-        match left {
-            true => {
-                self.grid.insert(0, col);
-                self.sand_drop_x += 1;
-                Sand::new(true, 0, self.max_row - 1)
-            }
-            false => {
-                self.grid.push(col);
-                Sand::new(true, self.max_col, self.max_row - 1)
+    fn next(&self, x: i32, y: i32) -> Result<(i32, i32), OutOfBoundsError> {
+        for &(mx, my) in self.rules.moves() {
+            for (k, (dx, dy)) in self.rules.reach((mx, my)).enumerate() {
+                if k > 0 && !self.is_empty(x + dx - mx, y) {
+                    break;
+                }
+                match self.check(x, y, (dx, dy)) {
+                    Ok(false) => {}
+                    Ok(true) => return Ok((x + dx, y + dy)),
+                    Err(e) => return Err(e),
+                };
             }
         }
+        Ok((x, y))
+    }
+
+    fn check(&self, x: i32, y: i32, (dx, dy): (i32, i32)) -> Result<bool, OutOfBoundsError> {
+        if self.max_row < y + dy {
+            return Err(OutOfBoundsError::Bottom);
+        }
+        Ok(self.is_empty(x + dx, y + dy))
     }
-}
 
-fn get_difference(x1: usize, x2: usize) -> isize {
-    match x1.cmp(&x2) {
-        Ordering::Less => 1,
-        Ordering::Equal => 0,
-        Ordering::Greater => -1,
+    fn is_empty(&self, x: i32, y: i32) -> bool {
+        matches!(self.get_grid_value(x, y), b'.')
     }
 }
 
-fn to_coords(s: &str) -> (usize, usize) {
+fn to_coords(s: &str) -> (i32, i32) {
     let (x, y) = s.split_once(',').unwrap();
-    (x.parse::<usize>().unwrap(), y.parse::<usize>().unwrap())
+    (x.parse::<i32>().unwrap(), y.parse::<i32>().unwrap())
 }
 
 pub fn part1(s: &str) -> u32 {
-    let mut cave = Cave::from_string(s, false);
-    let mut total = 0;
-
-    loop {
-        let sand = cave.drop_sand();
-        if !sand.dropped {
-            return total;
-        }
-        total += 1;
-    }
+    Cave::from_string(s, false).fill()
 }
 
 pub fn part2(s: &str) -> u32 {
-    let mut cave = Cave::from_string(s, true);
-    let mut total = 0;
-
-    loop {
-        let sand = cave.drop_sand();
-        total += 1;
-
-        if sand.x == cave.sand_drop_x && sand.y == 0 {
-            return total;
-        }
-    }
+    Cave::from_string(s, true).settle()
 }
 
 #[cfg(test)]
@@ -255,14 +305,29 @@ mod tests {
 
     use super::*;
 
+    fn shift(s: &str, dx: i32, dy: i32) -> String {
+        s.lines()
+            .map(|l| {
+                l.split(" -> ")
+                    .map(|c| {
+                        let (x, y) = to_coords(c);
+                        format!("{},{}", x + dx, y + dy)
+                    })
+                    .collect::<Vec<_>>()
+                    .join(" -> ")
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
     #[test]
     fn test_load_cave() -> Result<(), String> {
         let s = fs::read_to_string("test_input.txt").expect("File not found");
         let cave = Cave::from_string(&s, false);
 
         assert_eq!(cave.max_row, 9);
-        assert_eq!(cave.max_col, 9);
-        assert_eq!(cave.get_grid_value(4, 4), b'#');
+        assert_eq!(cave.bounds(), ((494, 0), (503, 9)));
+        assert_eq!(cave.get_grid_value(498, 4), b'#');
         Ok(())
     }
 
@@ -272,6 +337,28 @@ mod tests {
 
         assert_eq!(x, 498);
         assert_eq!(y, 4);
+        assert_eq!(to_coords("-2,-7"), (-2, -7));
+        Ok(())
+    }
+
+    #[test]
+    fn test_drop_sand_with() -> Result<(), String> {
+        let s = fs::read_to_string("test_input.txt").expect("File not found");
+        let mut cave = Cave::from_string(&s, false);
+
+        let mut path = Vec::new();
+        let result = loop {
+            path.clear();
+            let sand = cave.drop_sand_with(|p| path.push(p));
+            if !sand.dropped {
+                break sand;
+            }
+        };
+
+        assert!(!cave.is_active());
+        assert_eq!(path[..3], [(500, 0), (500, 1), (499, 2)]);
+        assert_eq!(*path.last().unwrap(), (result.x, result.y));
+        assert_eq!(result.y, cave.max_row);
         Ok(())
     }
 
@@ -283,10 +370,8 @@ mod tests {
         let result = cave.drop_sand();
 
         assert!(result.dropped);
-        assert_eq!(
-            cave.get_grid_value(cave.sand_drop_x, cave.max_row - 1),
-            b'o'
-        );
+        assert_eq!((result.x, result.y), (500, 8));
+        assert_eq!(cave.get_grid_value(500, cave.max_row - 1), b'o');
         Ok(())
     }
 
@@ -295,13 +380,10 @@ mod tests {
         let s = fs::read_to_string("test_input.txt").expect("File not found");
         let cave = Cave::from_string(&s, false);
 
-        assert!(cave.check_down(cave.sand_drop_x, 0).unwrap());
-        assert!(!cave.check_down(4, 4).unwrap());
-        assert!(!cave.check_down(7, 8).unwrap());
-        assert_eq!(
-            cave.check_down(cave.max_col, cave.max_row),
-            Err(OutOfBoundsError::Bottom)
-        );
+        assert!(cave.check(500, 0, (0, 1)).unwrap());
+        assert!(!cave.check(498, 4, (0, 1)).unwrap());
+        assert!(!cave.check(501, 8, (0, 1)).unwrap());
+        assert_eq!(cave.check(503, 9, (0, 1)), Err(OutOfBoundsError::Bottom));
         Ok(())
     }
 
@@ -310,12 +392,10 @@ mod tests {
         let s = fs::read_to_string("test_input.txt").expect("File not found");
         let cave = Cave::from_string(&s, false);
 
-        assert_eq!(
-            cave.check_left(0, cave.max_row),
-            Err(OutOfBoundsError::Left)
-        );
-        assert!(cave.check_left(4, 4).unwrap());
-        assert!(!cave.check_left(3, 5).unwrap());
+        assert_eq!(cave.check(494, 9, (-1, 1)), Err(OutOfBoundsError::Bottom));
+        assert!(cave.check(494, 8, (-1, 1)).unwrap());
+        assert!(cave.check(498, 4, (-1, 1)).unwrap());
+        assert!(!cave.check(497, 5, (-1, 1)).unwrap());
         Ok(())
     }
 
@@ -324,12 +404,9 @@ mod tests {
         let s = fs::read_to_string("test_input.txt").expect("File not found");
         let cave = Cave::from_string(&s, false);
 
-        assert_eq!(
-            cave.check_right(cave.max_col, 4),
-            Err(OutOfBoundsError::Right)
-        );
-        assert!(cave.check_right(4, 4).unwrap());
-        assert!(!cave.check_right(3, 5).unwrap());
+        assert!(cave.check(503, 4, (1, 1)).unwrap());
+        assert!(cave.check(498, 4, (1, 1)).unwrap());
+        assert!(!cave.check(497, 5, (1, 1)).unwrap());
         Ok(())
     }
 
@@ -339,12 +416,9 @@ mod tests {
         let cave = Cave::from_string(&s, false);
         cave.print_grid();
 
-        assert_eq!(cave.next(7, 8).unwrap(), (7, 8));
-        assert_eq!(
-            cave.next(cave.sand_drop_x, 0).unwrap(),
-            (cave.sand_drop_x, 1)
-        );
-        assert_eq!(cave.next(4, 4).unwrap(), (3, 5));
+        assert_eq!(cave.next(501, 8).unwrap(), (501, 8));
+        assert_eq!(cave.next(500, 0).unwrap(), (500, 1));
+        assert_eq!(cave.next(498, 4).unwrap(), (497, 5));
         Ok(())
     }
 
@@ -358,45 +432,209 @@ mod tests {
     }
 
     #[test]
-    fn test_increase_width() -> Result<(), String> {
+    fn test_floor() -> Result<(), String> {
         let s = fs::read_to_string("test_input.txt").expect("File not found");
         let mut cave = Cave::from_string(&s, true);
 
-        let sand_drop_x = cave.sand_drop_x;
-        let max_col = cave.max_col;
+        assert_eq!(cave.get_grid_value(-1000, 11), b'#');
+        assert_eq!(cave.get_grid_value(-1000, 10), b'.');
+
+        cave.fill();
+        assert_eq!(cave.get_grid_value(490, 10), b'o');
+        assert_eq!(cave.get_grid_value(489, 10), b'.');
+        assert_eq!(cave.get_grid_value(510, 10), b'o');
+        assert_eq!(cave.bounds(), ((490, 0), (510, 11)));
+        assert!(!cave.drop_sand().dropped);
+        Ok(())
+    }
+
+    #[test]
+    fn test_part2() -> Result<(), String> {
+        let s = fs::read_to_string("test_input.txt").expect("File not found");
+        let result = part2(&s);
+
+        assert_eq!(result, 93);
+        Ok(())
+    }
+
+    #[test]
+    fn test_settle() -> Result<(), String> {
+        for file in ["test_input.txt", "input.txt"] {
+            let s = fs::read_to_string(file).expect("File not found");
+            let mut simulated = Cave::from_string(&s, true);
+            let mut settled = Cave::from_string(&s, true);
+            assert_eq!(settled.settle(), simulated.fill());
+            assert_eq!(settled.bounds(), simulated.bounds());
+            let ((min_x, min_y), (max_x, max_y)) = simulated.bounds();
+            for y in min_y..=max_y {
+                for x in min_x..=max_x {
+                    assert_eq!(settled.get_grid_value(x, y), simulated.get_grid_value(x, y));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_settle_random_caves() -> Result<(), String> {
+        // Small linear congruential generator so the caves are reproducible.
+        let mut seed: u64 = 14;
+        let mut next = |n: i32| {
+            seed = seed
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (seed >> 33) as i32 % n
+        };
+        for _ in 0..50 {
+            let mut s = String::new();
+            for _ in 0..next(8) + 1 {
+                let (x, y) = (480 + next(40), 1 + next(20));
+                let (len, vertical) = (next(8), next(2) == 0);
+                let end = match vertical {
+                    true => (x, y + len),
+                    false => (x + len, y),
+                };
+                s.push_str(&format!("{},{} -> {},{}\n", x, y, end.0, end.1));
+            }
+            let source = (490 + next(20), 0);
+            let mut simulated = Cave::with_source(&s, true, source);
+            let mut settled = Cave::with_source(&s, true, source);
+            let expected = simulated.fill();
+            assert_eq!(settled.settle(), expected, "{}", s);
+
+            // Finishing off a partly filled cave settles the rest.
+            let mut partial = Cave::with_source(&s, true, source);
+            let dropped = (0..next(50))
+                .filter(|_| partial.drop_sand().dropped)
+                .count();
+            assert_eq!(dropped as u32 + partial.settle(), expected);
+        }
+        Ok(())
+    }
 
-        cave.increase_width(true);
+    #[test]
+    fn test_move_order() -> Result<(), String> {
+        let s = fs::read_to_string("test_input.txt").expect("File not found");
+        let mirrored = s
+            .lines()
+            .map(|l| {
+                l.split(" -> ")
+                    .map(|c| {
+                        let (x, y) = to_coords(c);
+                        format!("{},{}", 1000 - x, y)
+                    })
+                    .collect::<Vec<_>>()
+                    .join(" -> ")
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        let right_first =
+            Rules::new(vec![(0, 1), (1, 1), (-1, 1)], 1, 0).map_err(|e| e.to_string())?;
+        for (infinite_width, expected) in [(false, 24), (true, 93)] {
+            let mut cave = Cave::from_string(&mirrored, infinite_width);
+            cave.set_rules(right_first.clone());
+            assert_eq!(cave.fill(), expected);
+            let mut cave = Cave::from_string(&mirrored, infinite_width);
+            cave.set_rules(right_first.clone());
+            assert_eq!(cave.settle(), expected);
+        }
+        Ok(())
+    }
 
-        let mut result = vec![b'.'; cave.max_row - 1];
-        result.push(b'o');
-        result.push(b'#');
+    #[test]
+    fn test_slide() -> Result<(), String> {
+        let mut cave = Cave::with_source("", true, (0, 0));
+        assert_eq!(cave.fill(), 4);
+
+        let mut cave = Cave::with_source("", true, (0, 0));
+        let moves = Rules::default().moves().to_vec();
+        cave.set_rules(Rules::new(moves, 2, 0).map_err(|e| e.to_string())?);
+        assert_eq!(cave.fill(), 6);
+        assert_eq!(
+            render::render(&cave, cave.bounds(), &[]),
+            "..o..\nooooo\n#####\n"
+        );
+        Ok(())
+    }
 
-        assert_eq!(cave.grid[0], result);
-        assert_eq!(cave.sand_drop_x, sand_drop_x + 1);
-        assert_eq!(cave.max_col, max_col + 1);
+    #[test]
+    fn test_multiple_sources() -> Result<(), String> {
+        let mut cave = Cave::with_sources("", true, &[(0, 0), (10, 0)]);
+        assert_eq!(cave.fill(), 8);
 
-        let sand_drop_x = cave.sand_drop_x;
-        let max_col = cave.max_col;
-        cave.increase_width(false);
+        let mut cave = Cave::with_sources("", true, &[(0, 0), (1, 0)]);
+        assert_eq!(cave.settle(), 6);
+        assert_eq!(
+            render::render(&cave, cave.bounds(), &[]),
+            ".oo.\noooo\n####\n"
+        );
 
-        let mut result = vec![b'.'; cave.max_row - 1];
-        result.push(b'o');
-        result.push(b'#');
+        // Sources take turns, so the first grain from each lands side by side.
+        let s = "0,3 -> 20,3";
+        let mut cave = Cave::with_sources(s, false, &[(5, 0), (15, 0)]);
+        assert_eq!(
+            (cave.drop_sand().x, cave.drop_sand().x, cave.drop_sand().x),
+            (5, 15, 4)
+        );
+        Ok(())
+    }
 
-        let len = cave.grid.len();
-        assert_eq!(cave.grid[len - 1], result);
-        assert_eq!(cave.sand_drop_x, sand_drop_x);
-        assert_eq!(cave.max_col, max_col + 1);
+    #[test]
+    fn test_flow() -> Result<(), String> {
+        let s = "0,3 -> 10,3";
+        let mut cave = Cave::with_source(s, false, (5, 0));
+        assert_eq!(cave.fill(), 9);
+
+        // Flowing grains spread into a single layer until one runs off the end.
+        let mut cave = Cave::with_source(s, false, (5, 0));
+        let moves = Rules::default().moves().to_vec();
+        cave.set_rules(Rules::new(moves, 1, 4).map_err(|e| e.to_string())?);
+        let first = cave.drop_sand();
+        assert_eq!((first.x, first.y), (1, 2));
+        assert_eq!(cave.fill(), 8);
+        assert_eq!(
+            render::render(&cave, cave.bounds(), &[]),
+            "\
+.....+.....
+...........
+.ooooooooo.
+###########
+"
+        );
+        Ok(())
+    }
 
+    #[test]
+    fn test_negative_coordinates() -> Result<(), String> {
+        let s = fs::read_to_string("test_input.txt").expect("File not found");
+        let s = shift(&s, -600, -20);
+
+        let mut cave = Cave::with_source(&s, false, (-100, -20));
+        assert_eq!(cave.fill(), 24);
+        let mut cave = Cave::with_source(&s, true, (-100, -20));
+        assert_eq!(cave.fill(), 93);
+        let mut cave = Cave::with_source(&s, true, (-100, -20));
+        assert_eq!(cave.settle(), 93);
+        assert_eq!(cave.bounds(), ((-110, -20), (-90, -9)));
         Ok(())
     }
 
     #[test]
-    fn test_part2() -> Result<(), String> {
+    fn test_source() -> Result<(), String> {
         let s = fs::read_to_string("test_input.txt").expect("File not found");
-        let result = part2(&s);
 
-        assert_eq!(result, 93);
+        // Pouring in to the right of the rock misses it completely.
+        let mut cave = Cave::with_source(&s, false, (510, 0));
+        assert_eq!(cave.fill(), 0);
+        let mut cave = Cave::with_source(&s, true, (510, 0));
+        // The full 121 grain pile, less three rock cells in its corner and
+        // the two cells they shelter from above.
+        assert_eq!(cave.fill(), 121 - 3 - 2);
+
+        // A source inside the rock is buried from the start.
+        let mut cave = Cave::with_source(&s, true, (498, 5));
+        assert_eq!(cave.fill(), 0);
+        assert_eq!(cave.settle(), 0);
         Ok(())
     }
 }
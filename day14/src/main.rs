@@ -1,6 +1,6 @@
-use std::fs;
+use std::{env, fs, path::Path};
 
-use day14::{part1, part2};
+use day14::{part1, part2, render::export_frames};
 
 fn main() {
     let s = fs::read_to_string("input.txt").expect("File not found");
@@ -8,4 +8,16 @@ fn main() {
     println!("{}", result);
     let result = part2(&s);
     println!("{}", result);
+
+    if let Some(dir) = env::args().nth(1) {
+        let every = env::args()
+            .nth(2)
+            .map_or(500, |n| n.parse().expect("Invalid frame interval"));
+        for (mode, infinite_width) in [("abyss", false), ("floor", true)] {
+            let dir = Path::new(&dir).join(mode);
+            let frames =
+                export_frames(&s, infinite_width, every, 2, &dir).expect("Could not write frames");
+            println!("Wrote {} frames to {}", frames, dir.display());
+        }
+    }
 }
@@ -0,0 +1,254 @@
+use std::{
+    fs::{self, File},
+    io::{self, BufWriter, Write},
+    path::Path,
+};
+
+use crate::Cave;
+
+type Bounds = ((i32, i32), (i32, i32));
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum Tile {
+    Rock,
+    Sand,
+    Falling,
+    Source,
+    Air,
+}
+
+impl Tile {
+    fn to_char(self) -> char {
+        match self {
+            Tile::Rock => '#',
+            Tile::Sand => 'o',
+            Tile::Falling => '~',
+            Tile::Source => '+',
+            Tile::Air => '.',
+        }
+    }
+
+    fn to_rgb(self) -> [u8; 3] {
+        match self {
+            Tile::Rock => [105, 105, 105],
+            Tile::Sand => [238, 203, 173],
+            Tile::Falling => [220, 20, 60],
+            Tile::Source => [30, 144, 255],
+            Tile::Air => [16, 16, 16],
+        }
+    }
+}
+
+fn rows(cave: &Cave, bounds: Bounds, falling: &[(i32, i32)]) -> Vec<Vec<Tile>> {
+    let ((min_x, min_y), (max_x, max_y)) = bounds;
+    let mut rows: Vec<Vec<Tile>> = (min_y..=max_y)
+        .map(|y| {
+            (min_x..=max_x)
+                .map(|x| match cave.get_grid_value(x, y) {
+                    b'#' => Tile::Rock,
+                    b'o' => Tile::Sand,
+                    _ if cave.sources().contains(&(x, y)) => Tile::Source,
+                    _ => Tile::Air,
+                })
+                .collect()
+        })
+        .collect();
+    for &(x, y) in falling {
+        if (min_x..=max_x).contains(&x) && (min_y..=max_y).contains(&y) {
+            rows[(y - min_y) as usize][(x - min_x) as usize] = Tile::Falling;
+        }
+    }
+    rows
+}
+
+/// Draws the part of the cave inside `bounds`, with `falling` marking the
+/// path of a grain still on its way down.
+pub fn render(cave: &Cave, bounds: Bounds, falling: &[(i32, i32)]) -> String {
+    rows(cave, bounds, falling)
+        .iter()
+        .map(|r| r.iter().map(|t| t.to_char()).collect::<String>() + "\n")
+        .collect()
+}
+
+pub fn write_ppm<W: Write>(
+    cave: &Cave,
+    bounds: Bounds,
+    falling: &[(i32, i32)],
+    scale: usize,
+    w: &mut W,
+) -> io::Result<()> {
+    let rows = rows(cave, bounds, falling);
+    let width = rows[0].len() * scale;
+    let height = rows.len() * scale;
+    write!(w, "P6\n{} {}\n255\n", width, height)?;
+    for row in rows.iter() {
+        let line: Vec<u8> = row.iter().flat_map(|t| t.to_rgb().repeat(scale)).collect();
+        for _ in 0..scale {
+            w.write_all(&line)?;
+        }
+    }
+    w.flush()
+}
+
+// Drops sand until every source is done, calling `settled` with the cave
+// and the path of each grain that comes to rest. Returns the paths of the
+// grains that did not.
+fn pour(
+    cave: &mut Cave,
+    mut settled: impl FnMut(&Cave, &[(i32, i32)]) -> io::Result<()>,
+) -> io::Result<Vec<(i32, i32)>> {
+    let mut lost = Vec::new();
+    while cave.is_active() {
+        let mut path = Vec::new();
+        match cave.drop_sand_with(|p| path.push(p)).dropped {
+            true => settled(cave, &path)?,
+            false => lost.extend(path),
+        }
+    }
+    Ok(lost)
+}
+
+// Every frame shares the bounds of the finished cave so they line up when
+// played back. Without a floor that includes the path of the first grain to
+// fall out, and a spare column either side keeps the edges visible.
+fn final_bounds(s: &str, infinite_width: bool) -> Bounds {
+    let mut cave = Cave::from_string(s, infinite_width);
+    let ((min_x, min_y), (max_x, max_y)) = match infinite_width {
+        true => {
+            cave.settle();
+            cave.bounds()
+        }
+        false => {
+            let lost =
+                pour(&mut cave, |_, _| Ok(())).expect("Computing bounds does not write anything");
+            lost.iter()
+                .fold(cave.bounds(), |((a, b), (c, d)), &(x, y)| {
+                    ((a.min(x), b.min(y)), (c.max(x), d.max(y)))
+                })
+        }
+    };
+    ((min_x - 1, min_y), (max_x + 1, max_y))
+}
+
+/// Writes a frame after every `every` grains, showing the last grain's path,
+/// then `final.ppm` with the cave once no more sand will settle. An `every`
+/// of 0 is treated as 1. Floor or abyss mode is picked by `infinite_width`.
+/// Returns the number of images.
+pub fn export_frames(
+    s: &str,
+    infinite_width: bool,
+    every: usize,
+    scale: usize,
+    dir: &Path,
+) -> io::Result<usize> {
+    let every = every.max(1);
+    fs::create_dir_all(dir)?;
+    let bounds = final_bounds(s, infinite_width);
+    let mut cave = Cave::from_string(s, infinite_width);
+    let mut grains = 0;
+    let mut frames = 0;
+    let lost = pour(&mut cave, |cave, path| {
+        grains += 1;
+        if grains % every != 0 {
+            return Ok(());
+        }
+        let f = File::create(dir.join(format!("frame_{:05}.ppm", frames)))?;
+        frames += 1;
+        write_ppm(cave, bounds, path, scale, &mut BufWriter::new(f))
+    })?;
+    let f = File::create(dir.join("final.ppm"))?;
+    write_ppm(&cave, bounds, &lost, scale, &mut BufWriter::new(f))?;
+    Ok(frames + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs};
+
+    use super::*;
+
+    #[test]
+    fn test_render() -> Result<(), String> {
+        let s = fs::read_to_string("test_input.txt").expect("File not found");
+        let mut cave = Cave::from_string(&s, false);
+        assert_eq!(
+            render(&cave, cave.bounds(), &[]),
+            "\
+......+...
+..........
+..........
+..........
+....#...##
+....#...#.
+..###...#.
+........#.
+........#.
+#########.
+"
+        );
+
+        let lost = pour(&mut cave, |_, _| Ok(())).unwrap();
+        assert_eq!(
+            render(&cave, final_bounds(&s, false), &lost),
+            "\
+........~....
+........~....
+.......~o....
+......~ooo...
+.....~#ooo##.
+....~o#ooo#..
+...~###ooo#..
+...~..oooo#..
+..~o.ooooo#..
+.~#########..
+"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_final_bounds() -> Result<(), String> {
+        let s = fs::read_to_string("test_input.txt").expect("File not found");
+        assert_eq!(final_bounds(&s, false), ((492, 0), (504, 9)));
+        assert_eq!(final_bounds(&s, true), ((489, 0), (511, 11)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_write_ppm() -> Result<(), String> {
+        let s = fs::read_to_string("test_input.txt").expect("File not found");
+        let cave = Cave::from_string(&s, false);
+
+        let mut out = Vec::new();
+        write_ppm(&cave, cave.bounds(), &[(494, 0)], 2, &mut out).unwrap();
+        let header = b"P6\n20 20\n255\n";
+
+        assert_eq!(&out[..header.len()], header);
+        assert_eq!(out.len(), header.len() + 20 * 20 * 3);
+        assert_eq!(&out[header.len()..header.len() + 3], &[220, 20, 60]);
+        assert_eq!(&out[header.len() + 6..header.len() + 9], &[16, 16, 16]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_export_frames() -> Result<(), String> {
+        let s = fs::read_to_string("test_input.txt").expect("File not found");
+        let dir = env::temp_dir().join(format!("day14_frames_{}", std::process::id()));
+
+        for (infinite_width, frames) in [(false, 24 / 5 + 1), (true, 93 / 5 + 1)] {
+            let _ = fs::remove_dir_all(&dir);
+            assert_eq!(
+                export_frames(&s, infinite_width, 5, 1, &dir).unwrap(),
+                frames
+            );
+            assert!(dir.join("frame_00000.ppm").exists());
+            assert!(dir.join("final.ppm").exists());
+            assert_eq!(fs::read_dir(&dir).unwrap().count(), frames);
+        }
+
+        let _ = fs::remove_dir_all(&dir);
+        assert_eq!(export_frames(&s, false, 0, 1, &dir).unwrap(), 24 + 1);
+        fs::remove_dir_all(&dir).unwrap();
+        Ok(())
+    }
+}
@@ -0,0 +1,110 @@
+use std::{error::Error, fmt};
+
+/// How a grain of sand moves. Each step the grain takes the first of `moves`
+/// that leads to an empty cell; every move goes down exactly one row, so a
+/// grain can never skip over rock.
+///
+/// A sideways move may carry the grain up to `slide` cells across, as long as
+/// the cells it slides over on its current row are empty. A grain with no
+/// move left can still `flow` up to that many cells along flat ground,
+/// keeping to the first direction it picks, before falling again.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Rules {
+    moves: Vec<(i32, i32)>,
+    slide: u32,
+    flow: u32,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum RulesError {
+    InvalidMove(i32, i32),
+    NoSlide,
+}
+
+impl fmt::Display for RulesError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::InvalidMove(dx, dy) => {
+                write!(f, "move ({}, {}) does not go down one row", dx, dy)
+            }
+            Self::NoSlide => write!(f, "sideways moves must slide at least one cell"),
+        }
+    }
+}
+
+impl Error for RulesError {}
+
+impl Default for Rules {
+    fn default() -> Self {
+        Self {
+            moves: vec![(0, 1), (-1, 1), (1, 1)],
+            slide: 1,
+            flow: 0,
+        }
+    }
+}
+
+impl Rules {
+    pub fn new(moves: Vec<(i32, i32)>, slide: u32, flow: u32) -> Result<Self, RulesError> {
+        if let Some(&(dx, dy)) = moves.iter().find(|&&(_, dy)| dy != 1) {
+            return Err(RulesError::InvalidMove(dx, dy));
+        }
+        if slide == 0 {
+            return Err(RulesError::NoSlide);
+        }
+        Ok(Self { moves, slide, flow })
+    }
+
+    pub fn moves(&self) -> &[(i32, i32)] {
+        &self.moves
+    }
+
+    pub fn slide(&self) -> u32 {
+        self.slide
+    }
+
+    pub fn flow(&self) -> u32 {
+        self.flow
+    }
+
+    /// Offsets a move can reach once sliding is allowed for, nearest first.
+    pub(crate) fn reach(&self, (dx, dy): (i32, i32)) -> impl Iterator<Item = (i32, i32)> {
+        let distance = match dx {
+            0 => 1,
+            _ => i32::try_from(self.slide).unwrap_or(i32::MAX),
+        };
+        (1..=distance).map(move |k| (dx * k, dy))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_new() -> Result<(), String> {
+        let rules = Rules::new(vec![(0, 1), (1, 1), (-1, 1)], 2, 3).map_err(|e| e.to_string())?;
+        assert_eq!(rules.moves(), &[(0, 1), (1, 1), (-1, 1)]);
+        assert_eq!((rules.slide(), rules.flow()), (2, 3));
+
+        // Sideways moves never finish, and longer drops pass through rock.
+        assert_eq!(
+            Rules::new(vec![(1, 0)], 1, 0),
+            Err(RulesError::InvalidMove(1, 0))
+        );
+        assert_eq!(
+            Rules::new(vec![(0, 1), (0, 2)], 1, 0),
+            Err(RulesError::InvalidMove(0, 2))
+        );
+        assert_eq!(
+            Rules::new(vec![(0, -1)], 1, 0),
+            Err(RulesError::InvalidMove(0, -1))
+        );
+        assert_eq!(
+            Rules::new(vec![(0, 1), (-1, 1)], 0, 0),
+            Err(RulesError::NoSlide)
+        );
+        Ok(())
+    }
+}
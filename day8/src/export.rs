@@ -0,0 +1,140 @@
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::Path,
+};
+
+use crate::{Sightlines, TreePatch};
+
+const VISIBLE: [u8; 3] = [34, 139, 34];
+const HIDDEN: [u8; 3] = [64, 64, 64];
+const BEST: [u8; 3] = [220, 20, 60];
+
+pub fn export(t: &TreePatch, dir: &Path) -> io::Result<()> {
+    let sightlines = t.sightlines();
+    let mut pgm = BufWriter::new(File::create(dir.join("scenic_scores.pgm"))?);
+    write_scenic_pgm(sightlines, &mut pgm)?;
+    let mut ppm = BufWriter::new(File::create(dir.join("visibility.ppm"))?);
+    write_visibility_ppm(sightlines, &mut ppm)?;
+    let mut csv = BufWriter::new(File::create(dir.join("trees.csv"))?);
+    write_csv(t, sightlines, &mut csv)
+}
+
+fn write_csv<W: Write>(t: &TreePatch, sightlines: &Sightlines, w: &mut W) -> io::Result<()> {
+    writeln!(w, "x,y,height,visible,scenic_score")?;
+    for (y, row) in t.trees.iter().enumerate() {
+        for (x, tree) in row.iter().enumerate() {
+            writeln!(
+                w,
+                "{},{},{},{},{}",
+                x, y, tree, sightlines.visible[y][x], sightlines.scores[y][x]
+            )?;
+        }
+    }
+    w.flush()
+}
+
+fn max_score(sightlines: &Sightlines) -> u64 {
+    sightlines
+        .scores
+        .iter()
+        .flat_map(|r| r.iter())
+        .copied()
+        .max()
+        .unwrap_or(0)
+}
+
+fn write_scenic_pgm<W: Write>(sightlines: &Sightlines, w: &mut W) -> io::Result<()> {
+    let height = sightlines.scores.len();
+    let width = sightlines.scores[0].len();
+    let max = max_score(sightlines).max(1);
+    write!(w, "P5\n{} {}\n255\n", width, height)?;
+    for row in sightlines.scores.iter() {
+        let pixels: Vec<u8> = row
+            .iter()
+            .map(|&score| (score.saturating_mul(255) / max) as u8)
+            .collect();
+        w.write_all(&pixels)?;
+    }
+    w.flush()
+}
+
+fn write_visibility_ppm<W: Write>(sightlines: &Sightlines, w: &mut W) -> io::Result<()> {
+    let height = sightlines.visible.len();
+    let width = sightlines.visible[0].len();
+    let max = max_score(sightlines);
+    write!(w, "P6\n{} {}\n255\n", width, height)?;
+    for (visible_row, score_row) in sightlines.visible.iter().zip(sightlines.scores.iter()) {
+        let pixels: Vec<u8> = visible_row
+            .iter()
+            .zip(score_row.iter())
+            .flat_map(|(&visible, &score)| match (visible, score) {
+                (_, s) if s == max && max > 0 => BEST,
+                (true, _) => VISIBLE,
+                (false, _) => HIDDEN,
+            })
+            .collect();
+        w.write_all(&pixels)?;
+    }
+    w.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    #[test]
+    fn test_write_csv() -> Result<(), String> {
+        let s = fs::read_to_string("test_input.txt").expect("File not found");
+        let t = TreePatch::load(&s);
+
+        let mut out = Vec::new();
+        write_csv(&t, t.sightlines(), &mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        let lines: Vec<_> = out.lines().collect();
+
+        assert_eq!(lines.len(), 26);
+        assert_eq!(lines[0], "x,y,height,visible,scenic_score");
+        assert_eq!(lines[1], "0,0,3,true,0");
+        assert_eq!(lines[5 * 3 + 2 + 1], "2,3,5,true,8");
+        Ok(())
+    }
+
+    #[test]
+    fn test_write_scenic_pgm() -> Result<(), String> {
+        let s = fs::read_to_string("test_input.txt").expect("File not found");
+        let t = TreePatch::load(&s);
+
+        let mut out = Vec::new();
+        write_scenic_pgm(t.sightlines(), &mut out).unwrap();
+        let header = b"P5\n5 5\n255\n";
+
+        assert_eq!(&out[..header.len()], header);
+        assert_eq!(out.len(), header.len() + 25);
+        assert_eq!(out[header.len() + 5 * 3 + 2], 255);
+        assert_eq!(out[header.len() + 5 + 2], 127);
+        Ok(())
+    }
+
+    #[test]
+    fn test_write_visibility_ppm() -> Result<(), String> {
+        let s = fs::read_to_string("test_input.txt").expect("File not found");
+        let t = TreePatch::load(&s);
+
+        let mut out = Vec::new();
+        write_visibility_ppm(t.sightlines(), &mut out).unwrap();
+        let header = b"P6\n5 5\n255\n";
+        let pixel = |x: usize, y: usize| {
+            let i = header.len() + (y * 5 + x) * 3;
+            [out[i], out[i + 1], out[i + 2]]
+        };
+
+        assert_eq!(out.len(), header.len() + 75);
+        assert_eq!(pixel(0, 0), VISIBLE);
+        assert_eq!(pixel(2, 3), BEST);
+        assert_eq!(pixel(3, 1), HIDDEN);
+        Ok(())
+    }
+}
@@ -1,134 +1,217 @@
-use std::{cmp, fs};
+use std::{cell::OnceCell, cmp, env, fs, path::Path};
+
+mod export;
 
 struct TreePatch {
     trees: Vec<Vec<u32>>,
+    diagonals: bool,
+    sightlines: OnceCell<Sightlines>,
+}
+
+// Scores are products of up to eight viewing distances, which can overflow even
+// a u64 in a large forest with diagonals on. They saturate rather than wrap.
+struct Sightlines {
+    visible: Vec<Vec<bool>>,
+    scores: Vec<Vec<u64>>,
 }
 
 impl TreePatch {
+    fn new(trees: Vec<Vec<u32>>, diagonals: bool) -> Self {
+        TreePatch {
+            trees,
+            diagonals,
+            sightlines: OnceCell::new(),
+        }
+    }
+
+    // One digit per tree, as in the puzzle input.
     fn load(s: &str) -> Self {
-        let patch: Vec<Vec<_>> = s
+        let patch = s
             .lines()
+            .filter(|l| !l.trim().is_empty())
             .map(|l| l.chars().map(|c| c.to_digit(10).unwrap()).collect())
             .collect();
+        TreePatch::new(patch, false)
+    }
+
+    // Whitespace separated heights, which may have any number of digits.
+    fn load_separated(s: &str) -> Self {
+        let patch = s
+            .lines()
+            .filter(|l| !l.trim().is_empty())
+            .map(|l| {
+                l.split_whitespace()
+                    .map(|h| h.parse::<u32>().unwrap())
+                    .collect()
+            })
+            .collect();
+        TreePatch::new(patch, false)
+    }
 
-        TreePatch { trees: patch }
+    fn with_diagonals(self) -> Self {
+        TreePatch::new(self.trees, true)
     }
 
     fn get_visible_count(&self) -> usize {
-        let height = self.trees.len();
-        let width = self.trees[0].len();
-        let mut visible_count = 0;
-        for i in 0..height {
-            for j in 0..width {
-                if i == 0 || i == height - 1 {
-                    visible_count += 1;
-                    continue;
-                } else if j == 0 || j == width - 1 {
-                    visible_count += 1;
-                    continue;
-                }
-                visible_count += self.check_visible(i, j)
-            }
-        }
-        visible_count
+        self.visibility_matrix()
+            .iter()
+            .map(|r| r.iter().filter(|&&v| v).count())
+            .sum()
+    }
+
+    fn get_max_scenic_score(&self) -> u64 {
+        self.scenic_score_matrix()
+            .iter()
+            .flat_map(|r| r.iter())
+            .fold(0, |acc, &e| cmp::max(acc, e))
     }
 
-    fn check_visible(&self, y: usize, x: usize) -> usize {
+    fn visibility_matrix(&self) -> &[Vec<bool>] {
+        &self.sightlines().visible
+    }
+
+    fn scenic_score_matrix(&self) -> &[Vec<u64>] {
+        &self.sightlines().scores
+    }
+
+    // Both parts and the exports read the same scan, so it only runs once.
+    fn sightlines(&self) -> &Sightlines {
+        self.sightlines.get_or_init(|| self.scan())
+    }
+
+    fn scan(&self) -> Sightlines {
         let height = self.trees.len();
         let width = self.trees[0].len();
-        let mut visible = true;
-        for i in 0..y {
-            if self.trees[i][x] >= self.trees[y][x] {
-                visible = false;
-                break;
-            };
-        }
-        if visible {
-            return 1;
-        }
-        visible = true;
-        for i in y + 1..height {
-            if self.trees[i][x] >= self.trees[y][x] {
-                visible = false;
-                break;
-            }
-        }
-        if visible {
-            return 1;
-        }
-        visible = true;
-        for i in 0..x {
-            if self.trees[y][i] >= self.trees[y][x] {
-                visible = false;
-                break;
-            }
-        }
-        if visible {
-            return 1;
-        }
-        visible = true;
-        for i in x + 1..width {
-            if self.trees[y][i] >= self.trees[y][x] {
-                visible = false;
-                break;
-            }
-        }
-        if visible {
-            return 1;
+        let mut sightlines = Sightlines {
+            visible: vec![vec![false; width]; height],
+            scores: vec![vec![1; width]; height],
+        };
+
+        for (start, step) in self.line_starts() {
+            let mut line = self.walk(start, step);
+            self.scan_line(&line, &mut sightlines);
+            line.reverse();
+            self.scan_line(&line, &mut sightlines);
         }
-        0
+        sightlines
     }
 
-    fn get_scenic_score(&self, x: usize, y: usize) -> usize {
-        let mut results = vec![0, 0, 0, 0];
+    // Every row, column and (optionally) diagonal exactly once; each is scanned
+    // in both directions by `sightlines`.
+    fn line_starts(&self) -> Vec<((usize, usize), (isize, isize))> {
         let height = self.trees.len();
         let width = self.trees[0].len();
-        for i in (0..x).rev() {
-            results[0] += 1;
-            if self.trees[y][i] >= self.trees[y][x] {
-                break;
-            }
+        let mut starts: Vec<_> = (0..height).map(|y| ((0, y), (1, 0))).collect();
+        starts.extend((0..width).map(|x| ((x, 0), (0, 1))));
+        if self.diagonals {
+            starts.extend((0..height).map(|y| ((0, y), (1, 1))));
+            starts.extend((1..width).map(|x| ((x, 0), (1, 1))));
+            starts.extend((0..height).map(|y| ((width - 1, y), (-1, 1))));
+            starts.extend((0..width - 1).map(|x| ((x, 0), (-1, 1))));
         }
-        for i in x + 1..width {
-            results[1] += 1;
-            if self.trees[y][i] >= self.trees[y][x] {
-                break;
-            }
+        starts
+    }
+
+    fn walk(&self, start: (usize, usize), step: (isize, isize)) -> Vec<(usize, usize)> {
+        let mut line = Vec::new();
+        let mut next = Some(start);
+        while let Some((x, y)) = next {
+            line.push((x, y));
+            next = self.step(x, y, step);
         }
-        for i in (0..y).rev() {
-            results[2] += 1;
-            if self.trees[i][x] >= self.trees[y][x] {
-                break;
-            }
+        line
+    }
+
+    fn step(&self, x: usize, y: usize, (dx, dy): (isize, isize)) -> Option<(usize, usize)> {
+        let x = x.checked_add_signed(dx)?;
+        let y = y.checked_add_signed(dy)?;
+        if y >= self.trees.len() || x >= self.trees[0].len() {
+            return None;
         }
-        for i in y + 1..height {
-            results[3] += 1;
-            if self.trees[i][x] >= self.trees[y][x] {
-                break;
+        Some((x, y))
+    }
+
+    // Looks back along `line` from each tree using a stack of the trees that are
+    // still tall enough to block something, so each line is handled in O(len).
+    fn scan_line(&self, line: &[(usize, usize)], sightlines: &mut Sightlines) {
+        let mut stack: Vec<usize> = Vec::new();
+        for (i, &(x, y)) in line.iter().enumerate() {
+            let tree = self.trees[y][x];
+            while let Some(&top) = stack.last() {
+                let (top_x, top_y) = line[top];
+                if self.trees[top_y][top_x] >= tree {
+                    break;
+                }
+                stack.pop();
             }
+            let distance = match stack.last() {
+                Some(&top) => i - top,
+                None => {
+                    sightlines.visible[y][x] = true;
+                    i
+                }
+            };
+            sightlines.scores[y][x] = sightlines.scores[y][x].saturating_mul(distance as u64);
+            stack.push(i);
         }
-        results.iter().fold(1, |acc, &e| acc * e)
     }
 
-    fn get_max_scenic_score(&self) -> usize {
-        let mut max_score = 0;
-        let height = self.trees.len();
-        let width = self.trees[0].len();
-        for y in 0..height {
-            for x in 0..width {
-                let score = self.get_scenic_score(x, y);
-                max_score = cmp::max(max_score, score);
-            }
+    #[cfg(test)]
+    fn directions(&self) -> Vec<(isize, isize)> {
+        let mut directions = vec![(1, 0), (-1, 0), (0, 1), (0, -1)];
+        if self.diagonals {
+            directions.extend([(1, 1), (-1, -1), (-1, 1), (1, -1)]);
         }
-        max_score
+        directions
+    }
+
+    #[cfg(test)]
+    fn check_visible(&self, y: usize, x: usize) -> bool {
+        let tree = self.trees[y][x];
+        self.directions().into_iter().any(|step| {
+            self.walk((x, y), step)[1..]
+                .iter()
+                .all(|&(i, j)| self.trees[j][i] < tree)
+        })
+    }
+
+    #[cfg(test)]
+    fn get_scenic_score(&self, x: usize, y: usize) -> u64 {
+        let tree = self.trees[y][x];
+        self.directions()
+            .into_iter()
+            .map(|step| {
+                let line = self.walk((x, y), step);
+                match line[1..]
+                    .iter()
+                    .position(|&(i, j)| self.trees[j][i] >= tree)
+                {
+                    Some(p) => p as u64 + 1,
+                    None => line.len() as u64 - 1,
+                }
+            })
+            .fold(1, u64::saturating_mul)
     }
 }
 
+// Usage: day8 [--separated] [--diagonals] [export dir]
 fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let flag = |name: &str| args.iter().any(|a| a == name);
     let content = fs::read_to_string("input.txt").expect("File not found");
-    let t = TreePatch::load(&content);
+    let mut t = match flag("--separated") {
+        true => TreePatch::load_separated(&content),
+        false => TreePatch::load(&content),
+    };
+    if flag("--diagonals") {
+        t = t.with_diagonals();
+    }
     part1(&t);
     part2(&t);
+
+    if let Some(dir) = args.iter().find(|a| !a.starts_with("--")) {
+        export::export(&t, Path::new(&dir)).expect("Could not write exports");
+    }
 }
 
 fn part1(t: &TreePatch) {
@@ -152,6 +235,20 @@ mod tests {
 
         let t = TreePatch::load(&s);
         assert_eq!(t.get_visible_count(), 21);
+
+        let visible = t.visibility_matrix();
+        assert_eq!(
+            [visible[1][1], visible[1][2], visible[1][3]],
+            [true, true, false]
+        );
+        assert_eq!(
+            [visible[2][1], visible[2][2], visible[2][3]],
+            [true, false, true]
+        );
+        assert_eq!(
+            [visible[3][1], visible[3][2], visible[3][3]],
+            [false, true, false]
+        );
         Ok(())
     }
 
@@ -160,8 +257,9 @@ mod tests {
         let s = fs::read_to_string("test_input.txt").expect("File not found");
 
         let t = TreePatch::load(&s);
-        assert_eq!(t.get_scenic_score(2, 1), 4);
-        assert_eq!(t.get_scenic_score(2, 3), 8);
+        let scores = t.scenic_score_matrix();
+        assert_eq!(scores[1][2], 4);
+        assert_eq!(scores[3][2], 8);
         Ok(())
     }
 
@@ -173,4 +271,76 @@ mod tests {
         assert_eq!(t.get_max_scenic_score(), 8);
         Ok(())
     }
+
+    #[test]
+    fn test_matrices_match_direct_scan() -> Result<(), String> {
+        let s = fs::read_to_string("input.txt").expect("File not found");
+
+        for t in [TreePatch::load(&s), TreePatch::load(&s).with_diagonals()] {
+            let visible = t.visibility_matrix();
+            let scores = t.scenic_score_matrix();
+            for y in 0..t.trees.len() {
+                for x in 0..t.trees[0].len() {
+                    assert_eq!(visible[y][x], t.check_visible(y, x));
+                    assert_eq!(scores[y][x], t.get_scenic_score(x, y));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_large_forest() -> Result<(), String> {
+        let size = 500;
+        let trees = (0..size)
+            .map(|y| (0..size).map(|x| ((x * 7 + y * 13) % 10) as u32).collect())
+            .collect();
+
+        let t = TreePatch::new(trees, false);
+        let scores = t.scenic_score_matrix();
+        assert_eq!(scores.len(), size);
+        assert_eq!(scores[0][0], 0);
+        assert!(t.get_visible_count() >= 4 * (size - 1));
+        Ok(())
+    }
+
+    #[test]
+    fn test_load_separated_heights() -> Result<(), String> {
+        let t = TreePatch::load_separated("10 2 33\n4 150 6\n");
+
+        assert_eq!(t.trees, vec![vec![10, 2, 33], vec![4, 150, 6]]);
+        assert_eq!(t.get_visible_count(), 6);
+
+        // A single column has no whitespace inside its lines, but the format
+        // is chosen by the caller so it still reads as whole numbers.
+        let t = TreePatch::load_separated("10\n20\n");
+        assert_eq!(t.trees, vec![vec![10], vec![20]]);
+        let t = TreePatch::load("10\n20\n");
+        assert_eq!(t.trees, vec![vec![1, 0], vec![2, 0]]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_diagonals() -> Result<(), String> {
+        let s = fs::read_to_string("test_input.txt").expect("File not found");
+        let t = TreePatch::load(&s).with_diagonals();
+        assert_eq!(t.scenic_score_matrix()[3][2], 16);
+        assert_eq!(t.get_visible_count(), 22);
+        Ok(())
+    }
+
+    #[test]
+    fn test_score_saturates() -> Result<(), String> {
+        // The centre tree sees 300 trees in each of 8 directions, and 300^8
+        // does not fit in a u64.
+        let size = 601;
+        let mut trees = vec![vec![0; size]; size];
+        trees[300][300] = 1;
+
+        let t = TreePatch::new(trees, true);
+        assert_eq!(t.get_max_scenic_score(), u64::MAX);
+        assert_eq!(t.scenic_score_matrix()[300][300], u64::MAX);
+        assert_eq!(t.scenic_score_matrix()[300][301], 1);
+        Ok(())
+    }
 }
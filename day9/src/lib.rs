@@ -0,0 +1,410 @@
+use std::{collections::HashSet, fmt, str::FromStr};
+
+pub mod render;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum InstructionError {
+    UnknownDirection(String),
+    InvalidDistance(String),
+    Malformed(String),
+}
+
+impl fmt::Display for InstructionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::UnknownDirection(d) => write!(f, "'{}' is not a valid direction", d),
+            Self::InvalidDistance(d) => write!(f, "'{}' is not a valid distance", d),
+            Self::Malformed(l) => write!(f, "'{}' is not a valid instruction", l),
+        }
+    }
+}
+
+impl std::error::Error for InstructionError {}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum RopeError {
+    InvalidSlack(i32),
+}
+
+impl fmt::Display for RopeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::InvalidSlack(s) => write!(f, "slack must be at least 1, got {}", s),
+        }
+    }
+}
+
+impl std::error::Error for RopeError {}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
+    UpLeft,
+    UpRight,
+    DownLeft,
+    DownRight,
+}
+
+impl Direction {
+    fn offset(&self) -> (i32, i32) {
+        match self {
+            Self::Up => (0, -1),
+            Self::Down => (0, 1),
+            Self::Left => (-1, 0),
+            Self::Right => (1, 0),
+            Self::UpLeft => (-1, -1),
+            Self::UpRight => (1, -1),
+            Self::DownLeft => (-1, 1),
+            Self::DownRight => (1, 1),
+        }
+    }
+}
+
+impl FromStr for Direction {
+    type Err = InstructionError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "U" => Ok(Self::Up),
+            "D" => Ok(Self::Down),
+            "L" => Ok(Self::Left),
+            "R" => Ok(Self::Right),
+            "UL" => Ok(Self::UpLeft),
+            "UR" => Ok(Self::UpRight),
+            "DL" => Ok(Self::DownLeft),
+            "DR" => Ok(Self::DownRight),
+            _ => Err(InstructionError::UnknownDirection(s.to_string())),
+        }
+    }
+}
+
+fn parse_instruction(l: &str) -> Result<(Direction, u32), InstructionError> {
+    let parts: Vec<_> = l.split_whitespace().collect();
+    match parts[..] {
+        [direction, distance] => Ok((
+            direction.parse()?,
+            distance
+                .parse::<u32>()
+                .map_err(|_| InstructionError::InvalidDistance(distance.to_string()))?,
+        )),
+        _ => Err(InstructionError::Malformed(l.to_string())),
+    }
+}
+
+#[derive(Debug, Default, PartialEq, Eq, Hash, Clone, Copy)]
+pub struct Position {
+    pub x: i32,
+    pub y: i32,
+}
+
+impl Position {
+    pub fn new() -> Self {
+        Self { x: 0, y: 0 }
+    }
+
+    pub fn touching(&self, p: &Position) -> bool {
+        self.within(p, 1)
+    }
+
+    pub fn within(&self, p: &Position, slack: i32) -> bool {
+        (self.x - p.x).abs() <= slack && (self.y - p.y).abs() <= slack
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct BoundingBox {
+    pub min: Position,
+    pub max: Position,
+}
+
+impl BoundingBox {
+    fn around(p: Position) -> Self {
+        Self { min: p, max: p }
+    }
+
+    fn include(&mut self, p: &Position) {
+        self.min.x = self.min.x.min(p.x);
+        self.min.y = self.min.y.min(p.y);
+        self.max.x = self.max.x.max(p.x);
+        self.max.y = self.max.y.max(p.y);
+    }
+}
+
+#[derive(Debug)]
+pub struct Rope {
+    tail: Vec<Position>,
+    visited: Vec<HashSet<Position>>,
+    slack: i32,
+}
+
+impl Rope {
+    pub fn new(tail_length: usize) -> Self {
+        Self::build(tail_length, 1)
+    }
+
+    // With no slack a knot could never be touching the one ahead of it.
+    pub fn with_slack(tail_length: usize, slack: i32) -> Result<Self, RopeError> {
+        match slack {
+            1.. => Ok(Self::build(tail_length, slack)),
+            _ => Err(RopeError::InvalidSlack(slack)),
+        }
+    }
+
+    fn build(tail_length: usize, slack: i32) -> Self {
+        Self {
+            tail: vec![Position::new(); tail_length + 1],
+            visited: vec![HashSet::from([Position::new()]); tail_length + 1],
+            slack,
+        }
+    }
+
+    pub fn move_direction_distance(&mut self, direction: Direction, distance: u32) {
+        for _ in 0..distance {
+            self.move_direction(direction);
+        }
+    }
+
+    pub fn move_direction(&mut self, direction: Direction) {
+        let (dx, dy) = direction.offset();
+        self.tail[0].x += dx;
+        self.tail[0].y += dy;
+        for i in 1..self.tail.len() {
+            self.move_tail(i);
+        }
+        self.update_visited();
+    }
+
+    fn move_tail(&mut self, piece_to_move: usize) {
+        if self.tail[piece_to_move - 1].within(&self.tail[piece_to_move], self.slack) {
+            return;
+        }
+        if self.tail[piece_to_move - 1].x - self.tail[piece_to_move].x >= 1 {
+            self.tail[piece_to_move].x += 1
+        } else if self.tail[piece_to_move - 1].x - self.tail[piece_to_move].x <= -1 {
+            self.tail[piece_to_move].x -= 1
+        }
+        if self.tail[piece_to_move - 1].y - self.tail[piece_to_move].y >= 1 {
+            self.tail[piece_to_move].y += 1
+        } else if self.tail[piece_to_move - 1].y - self.tail[piece_to_move].y <= -1 {
+            self.tail[piece_to_move].y -= 1
+        }
+    }
+
+    fn update_visited(&mut self) {
+        for (knot, visited) in self.tail.iter().zip(self.visited.iter_mut()) {
+            visited.insert(*knot);
+        }
+    }
+
+    /// Number of positions `knot` has visited, or `None` past the end of the rope.
+    pub fn visited_count(&self, knot: usize) -> Option<usize> {
+        self.visited.get(knot).map(|v| v.len())
+    }
+
+    pub fn tail_visited_count(&self) -> usize {
+        self.visited[self.tail.len() - 1].len()
+    }
+
+    pub fn knot_bounding_box(&self, knot: usize) -> Option<BoundingBox> {
+        let mut bounds = BoundingBox::around(Position::new());
+        self.visited
+            .get(knot)?
+            .iter()
+            .for_each(|p| bounds.include(p));
+        Some(bounds)
+    }
+
+    pub fn bounding_box(&self) -> BoundingBox {
+        let mut bounds = BoundingBox::around(Position::new());
+        self.visited
+            .iter()
+            .flat_map(|v| v.iter())
+            .for_each(|p| bounds.include(p));
+        bounds
+    }
+
+    pub fn parse_instructions(&mut self, instructions: &str) -> Result<(), InstructionError> {
+        self.parse_instructions_with(instructions, |_| {})
+    }
+
+    pub fn parse_instructions_with<F>(
+        &mut self,
+        instructions: &str,
+        mut after_instruction: F,
+    ) -> Result<(), InstructionError>
+    where
+        F: FnMut(&Rope),
+    {
+        for l in instructions.lines() {
+            let (direction, distance) = parse_instruction(l)?;
+            self.move_direction_distance(direction, distance);
+            after_instruction(self);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::fs;
+
+    use super::*;
+
+    #[test]
+    fn test_position_touching() -> Result<(), String> {
+        let p = Position::new();
+        assert!(p.touching(&Position { x: 1, y: 1 }));
+        assert!(!p.touching(&Position { x: 1, y: 10 }));
+
+        let p2 = Position { x: 50, y: -100 };
+        assert!(p2.touching(&Position { x: 51, y: -100 }));
+        assert!(!p2.touching(&Position { x: 1, y: 10 }));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_moving() -> Result<(), String> {
+        let mut r = Rope::new(1);
+        r.move_direction(Direction::Up);
+        r.move_direction(Direction::Up);
+        r.move_direction(Direction::Left);
+        r.move_direction(Direction::Right);
+        r.move_direction(Direction::Right);
+        r.move_direction(Direction::Down);
+
+        assert_eq!(r.tail[0].x, 1);
+        assert_eq!(r.tail[0].y, -1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_moving_tail() -> Result<(), String> {
+        let mut r = Rope::new(1);
+        r.move_direction(Direction::Up);
+        assert_eq!(r.tail[1].x, 0);
+        assert_eq!(r.tail[1].y, 0);
+
+        r.move_direction(Direction::Left);
+        assert_eq!(r.tail[1].x, 0);
+        assert_eq!(r.tail[1].y, 0);
+
+        r.move_direction(Direction::Up);
+        assert_eq!(r.tail[1].x, -1);
+        assert_eq!(r.tail[1].y, -1);
+
+        r.move_direction(Direction::Up);
+        assert_eq!(r.tail[1].x, -1);
+        assert_eq!(r.tail[1].y, -2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_tail_visited() -> Result<(), String> {
+        let mut r = Rope::new(1);
+        r.move_direction_distance(Direction::Up, 2);
+        assert_eq!(r.tail_visited_count(), 2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_part1() -> Result<(), String> {
+        let s = fs::read_to_string("test_input.txt").expect("File not found");
+        let mut r = Rope::new(1);
+        r.parse_instructions(&s).unwrap();
+        assert_eq!(r.tail_visited_count(), 13);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_part2() -> Result<(), String> {
+        let s = fs::read_to_string("test_input.txt").expect("File not found");
+        let mut r = Rope::new(9);
+        r.parse_instructions(&s).unwrap();
+        assert_eq!(r.tail_visited_count(), 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_visited_per_knot() -> Result<(), String> {
+        let s = fs::read_to_string("test_input.txt").expect("File not found");
+        let mut r = Rope::new(9);
+        r.parse_instructions(&s).unwrap();
+
+        assert_eq!(r.visited_count(1), Some(13));
+        assert_eq!(r.visited_count(9), Some(1));
+        assert_eq!(r.visited_count(10), None);
+        assert_eq!(
+            r.knot_bounding_box(0),
+            Some(BoundingBox {
+                min: Position { x: 0, y: -4 },
+                max: Position { x: 5, y: 0 },
+            })
+        );
+        assert_eq!(Some(r.bounding_box()), r.knot_bounding_box(0));
+        assert_eq!(r.knot_bounding_box(10), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_diagonal_moves() -> Result<(), String> {
+        let mut r = Rope::new(1);
+        r.parse_instructions("UR 3\nDL 1").unwrap();
+
+        assert_eq!(r.tail[0], Position { x: 2, y: -2 });
+        assert_eq!(r.tail[1], Position { x: 2, y: -2 });
+        assert_eq!(r.tail_visited_count(), 3);
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_errors() -> Result<(), String> {
+        let mut r = Rope::new(1);
+
+        assert_eq!(
+            r.parse_instructions("X 3"),
+            Err(InstructionError::UnknownDirection(String::from("X")))
+        );
+        assert_eq!(
+            r.parse_instructions("U -3"),
+            Err(InstructionError::InvalidDistance(String::from("-3")))
+        );
+        assert_eq!(
+            r.parse_instructions("U"),
+            Err(InstructionError::Malformed(String::from("U")))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_slack() -> Result<(), String> {
+        let p = Position::new();
+        assert!(p.within(&Position { x: 2, y: -2 }, 2));
+        assert!(!p.within(&Position { x: 3, y: 0 }, 2));
+
+        let mut r = Rope::with_slack(1, 2).unwrap();
+        r.move_direction_distance(Direction::Right, 4);
+
+        assert_eq!(r.tail[1], Position { x: 2, y: 0 });
+        assert_eq!(r.tail_visited_count(), 3);
+
+        assert_eq!(
+            Rope::with_slack(1, 0).err(),
+            Some(RopeError::InvalidSlack(0))
+        );
+        assert_eq!(
+            Rope::with_slack(1, -1).err(),
+            Some(RopeError::InvalidSlack(-1))
+        );
+        Ok(())
+    }
+}
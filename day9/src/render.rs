@@ -0,0 +1,180 @@
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::Path,
+};
+
+use crate::{BoundingBox, InstructionError, Position, Rope};
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum Cell {
+    Knot(usize),
+    Start,
+    Trail,
+    Empty,
+}
+
+impl Cell {
+    fn to_char(self, knots: usize) -> char {
+        match self {
+            Cell::Knot(0) => 'H',
+            Cell::Knot(_) if knots == 2 => 'T',
+            Cell::Knot(i) => char::from_digit((i % 10) as u32, 10).unwrap(),
+            Cell::Start => 's',
+            Cell::Trail => '#',
+            Cell::Empty => '.',
+        }
+    }
+
+    fn to_rgb(self) -> [u8; 3] {
+        match self {
+            Cell::Knot(0) => [220, 20, 60],
+            Cell::Knot(_) => [255, 215, 0],
+            Cell::Start => [30, 144, 255],
+            Cell::Trail => [128, 128, 128],
+            Cell::Empty => [16, 16, 16],
+        }
+    }
+}
+
+fn cell(rope: &Rope, p: &Position) -> Cell {
+    if let Some(i) = rope.tail.iter().position(|k| k == p) {
+        return Cell::Knot(i);
+    }
+    if *p == Position::new() {
+        return Cell::Start;
+    }
+    if rope.visited[rope.tail.len() - 1].contains(p) {
+        return Cell::Trail;
+    }
+    Cell::Empty
+}
+
+fn rows(rope: &Rope, bounds: &BoundingBox) -> Vec<Vec<Cell>> {
+    (bounds.min.y..=bounds.max.y)
+        .map(|y| {
+            (bounds.min.x..=bounds.max.x)
+                .map(|x| cell(rope, &Position { x, y }))
+                .collect()
+        })
+        .collect()
+}
+
+pub fn render(rope: &Rope, bounds: &BoundingBox) -> String {
+    let knots = rope.tail.len();
+    rows(rope, bounds)
+        .iter()
+        .map(|r| r.iter().map(|c| c.to_char(knots)).collect::<String>() + "\n")
+        .collect()
+}
+
+pub fn write_ppm<W: Write>(
+    rope: &Rope,
+    bounds: &BoundingBox,
+    scale: usize,
+    w: &mut W,
+) -> io::Result<()> {
+    let rows = rows(rope, bounds);
+    let width = rows[0].len() * scale;
+    let height = rows.len() * scale;
+    write!(w, "P6\n{} {}\n255\n", width, height)?;
+    for row in rows.iter() {
+        let line: Vec<u8> = row.iter().flat_map(|c| c.to_rgb().repeat(scale)).collect();
+        for _ in 0..scale {
+            w.write_all(&line)?;
+        }
+    }
+    w.flush()
+}
+
+fn final_bounds(instructions: &str, tail_length: usize) -> Result<BoundingBox, InstructionError> {
+    let mut rope = Rope::new(tail_length);
+    rope.parse_instructions(instructions)?;
+    Ok(rope.bounding_box())
+}
+
+pub fn ascii_frames(
+    instructions: &str,
+    tail_length: usize,
+) -> Result<Vec<String>, InstructionError> {
+    let bounds = final_bounds(instructions, tail_length)?;
+    let mut frames = Vec::new();
+    let mut rope = Rope::new(tail_length);
+    rope.parse_instructions_with(instructions, |r| frames.push(render(r, &bounds)))?;
+    Ok(frames)
+}
+
+pub fn export_frames(
+    instructions: &str,
+    tail_length: usize,
+    scale: usize,
+    dir: &Path,
+) -> io::Result<usize> {
+    let bounds = final_bounds(instructions, tail_length)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    let mut rope = Rope::new(tail_length);
+    let mut frame = 0;
+    let mut result = Ok(());
+    rope.parse_instructions_with(instructions, |r| {
+        if result.is_err() {
+            return;
+        }
+        result = File::create(dir.join(format!("frame_{:05}.ppm", frame)))
+            .and_then(|f| write_ppm(r, &bounds, scale, &mut BufWriter::new(f)));
+        frame += 1;
+    })
+    .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    result.map(|_| frame)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+    use crate::Direction;
+
+    #[test]
+    fn test_render() -> Result<(), String> {
+        let s = fs::read_to_string("test_input.txt").expect("File not found");
+        let frames = ascii_frames(&s, 1).unwrap();
+
+        assert_eq!(frames.len(), 8);
+        assert_eq!(frames[0], "......\n......\n......\n......\ns##TH.\n");
+        assert_eq!(frames[7], "..##..\n...##.\n.TH##.\n....#.\ns###..\n");
+        Ok(())
+    }
+
+    #[test]
+    fn test_render_knots() -> Result<(), String> {
+        let mut r = Rope::new(9);
+        r.move_direction_distance(Direction::Right, 4);
+        let bounds = BoundingBox {
+            min: Position { x: 0, y: -4 },
+            max: Position { x: 5, y: 0 },
+        };
+
+        assert_eq!(
+            render(&r, &bounds),
+            "......\n......\n......\n......\n4321H.\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_write_ppm() -> Result<(), String> {
+        let mut r = Rope::new(1);
+        r.move_direction(Direction::Right);
+        let bounds = r.bounding_box();
+
+        let mut out = Vec::new();
+        write_ppm(&r, &bounds, 2, &mut out).unwrap();
+        let header = b"P6\n4 2\n255\n";
+
+        assert_eq!(&out[..header.len()], header);
+        assert_eq!(out.len(), header.len() + 4 * 2 * 3);
+        assert_eq!(&out[header.len()..header.len() + 3], &[255, 215, 0]);
+        assert_eq!(&out[header.len() + 6..header.len() + 9], &[220, 20, 60]);
+        Ok(())
+    }
+}